@@ -0,0 +1,131 @@
+use std::collections::BTreeMap;
+
+use criterion::{black_box, criterion_group, criterion_main, BatchSize, Criterion};
+use rusty_trees::radix_trie::RadixTrie;
+
+const NUM_KEYS: usize = 10_000;
+
+/// Deterministic pseudo-random bytes (xorshift), so runs are comparable
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    fn bytes(&mut self, len: usize) -> Vec<u8> {
+        (0..len).map(|_| self.next() as u8).collect()
+    }
+}
+
+/// Byte keys with a full 256-way fanout at the first two levels, and
+/// (half as many) missing keys to look up
+fn byte_keys() -> (Vec<Vec<u8>>, Vec<Vec<u8>>) {
+    let mut rng = Rng(0x2545_f491_4f6c_dd1d);
+    let keys = (0..NUM_KEYS).map(|_| rng.bytes(8)).collect();
+    let missing = (0..NUM_KEYS / 2).map(|_| rng.bytes(8)).collect();
+    (keys, missing)
+}
+
+fn bench_bytes(c: &mut Criterion) {
+    let (keys, missing) = byte_keys();
+    let trie: RadixTrie<Vec<u8>, usize> = keys.iter().cloned().zip(0..).collect();
+    let btree: BTreeMap<Vec<u8>, usize> = keys.iter().cloned().zip(0..).collect();
+
+    let mut group = c.benchmark_group("bytes");
+    group.bench_function("get", |b| {
+        b.iter(|| {
+            for key in &keys {
+                black_box(trie.get(&key[..]));
+            }
+        })
+    });
+    group.bench_function("get_missing", |b| {
+        b.iter(|| {
+            for key in &missing {
+                black_box(trie.get(&key[..]));
+            }
+        })
+    });
+    group.bench_function("get_btree_map", |b| {
+        b.iter(|| {
+            for key in &keys {
+                black_box(btree.get(key));
+            }
+        })
+    });
+    group.bench_function("insert", |b| {
+        b.iter_batched(
+            || keys.clone(),
+            |keys| {
+                let mut trie = RadixTrie::new();
+                for (value, key) in keys.into_iter().enumerate() {
+                    trie.insert(key, value);
+                }
+                trie
+            },
+            BatchSize::LargeInput,
+        )
+    });
+    group.bench_function("remove", |b| {
+        b.iter_batched(
+            || {
+                keys.iter()
+                    .cloned()
+                    .zip(0..)
+                    .collect::<RadixTrie<_, usize>>()
+            },
+            |mut trie| {
+                for key in &keys {
+                    black_box(trie.remove(&key[..]));
+                }
+                trie
+            },
+            BatchSize::LargeInput,
+        )
+    });
+    group.finish();
+}
+
+fn bench_strings(c: &mut Criterion) {
+    let mut rng = Rng(0x9e37_79b9_7f4a_7c15);
+    let alphabet = b"abcdefghijklmnopqrstuvwxyz";
+    let keys: Vec<String> = (0..NUM_KEYS)
+        .map(|_| {
+            let len = 4 + rng.next() as usize % 8;
+            (0..len)
+                .map(|_| alphabet[rng.next() as usize % alphabet.len()] as char)
+                .collect()
+        })
+        .collect();
+    let trie: RadixTrie<String, usize> = keys.iter().cloned().zip(0..).collect();
+
+    let mut group = c.benchmark_group("strings");
+    group.bench_function("get", |b| {
+        b.iter(|| {
+            for key in &keys {
+                black_box(trie.get(key.as_str()));
+            }
+        })
+    });
+    group.bench_function("insert", |b| {
+        b.iter_batched(
+            || keys.clone(),
+            |keys| {
+                let mut trie = RadixTrie::new();
+                for (value, key) in keys.into_iter().enumerate() {
+                    trie.insert(key, value);
+                }
+                trie
+            },
+            BatchSize::LargeInput,
+        )
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_bytes, bench_strings);
+criterion_main!(benches);
@@ -5,7 +5,7 @@ fn main() {
     let dest_path = Path::new("src/radix_trie/fuzzer_tests.rs");
     if !dest_path.exists() {
         fs::write(
-            &dest_path,
+            dest_path,
             r#"
         // this is an automatically generated file (see build.rs)
         // run ./fuzz/corpus_to_generated_tests after running the fuzzer
@@ -0,0 +1,155 @@
+//! Differential harness shared by the fuzz targets: runs a sequence of
+//! actions against a map under test and a `HashMap`, checking that they
+//! agree after every action, and that the map iterates over the same entries
+//! in order.
+
+use std::{collections::HashMap, fmt::Debug, hash::Hash};
+
+use arbitrary::Arbitrary;
+use rusty_trees::{
+    adaptive_radix_trie::AdaptiveRadixTrie,
+    radix_trie::{Key, RadixTrie},
+};
+
+macro_rules! check_same {
+    ($trie:ident, $truth:ident, $name:expr, $($lambda:tt)*) => {
+        let a = $trie.$($lambda)*;
+        let b = $truth.$($lambda)*;
+        if a != b {
+            panic!("method {} failed: {:?} != {:?}", $name, a, b);
+        }
+    };
+}
+
+#[derive(Arbitrary)]
+pub enum Action<K> {
+    Insert(K, usize),
+    Get(K),
+    ContainsKey(K),
+    Remove(K),
+    RemoveEntry(K),
+}
+
+/// Methods of a map under test, with the same signatures as `HashMap`'s
+pub trait FuzzMap<K> {
+    fn insert(&mut self, key: K, value: usize) -> Option<usize>;
+    fn get(&self, key: &K) -> Option<&usize>;
+    fn contains_key(&self, key: &K) -> bool;
+    fn remove(&mut self, key: &K) -> Option<usize>;
+    fn remove_entry(&mut self, key: &K) -> Option<(K, usize)>;
+    fn len(&self) -> usize;
+
+    /// Every entry, in the order the map iterates over them
+    fn entries(&self) -> Vec<(K, usize)>;
+}
+
+impl<P> FuzzMap<P> for RadixTrie<P, usize>
+where
+    P: Key,
+{
+    fn insert(&mut self, key: P, value: usize) -> Option<usize> {
+        RadixTrie::insert(self, key, value)
+    }
+
+    fn get(&self, key: &P) -> Option<&usize> {
+        RadixTrie::get(self, key)
+    }
+
+    fn contains_key(&self, key: &P) -> bool {
+        RadixTrie::contains_key(self, key)
+    }
+
+    fn remove(&mut self, key: &P) -> Option<usize> {
+        RadixTrie::remove(self, key)
+    }
+
+    fn remove_entry(&mut self, key: &P) -> Option<(P, usize)> {
+        RadixTrie::remove_entry(self, key)
+    }
+
+    fn len(&self) -> usize {
+        RadixTrie::len(self)
+    }
+
+    fn entries(&self) -> Vec<(P, usize)> {
+        self.iter().map(|(key, value)| (key, *value)).collect()
+    }
+}
+
+impl FuzzMap<Vec<u8>> for AdaptiveRadixTrie<usize> {
+    fn insert(&mut self, key: Vec<u8>, value: usize) -> Option<usize> {
+        AdaptiveRadixTrie::insert(self, key, value)
+    }
+
+    fn get(&self, key: &Vec<u8>) -> Option<&usize> {
+        AdaptiveRadixTrie::get(self, key)
+    }
+
+    fn contains_key(&self, key: &Vec<u8>) -> bool {
+        AdaptiveRadixTrie::contains_key(self, key)
+    }
+
+    fn remove(&mut self, key: &Vec<u8>) -> Option<usize> {
+        AdaptiveRadixTrie::remove(self, key)
+    }
+
+    fn remove_entry(&mut self, key: &Vec<u8>) -> Option<(Vec<u8>, usize)> {
+        AdaptiveRadixTrie::remove_entry(self, key)
+    }
+
+    fn len(&self) -> usize {
+        AdaptiveRadixTrie::len(self)
+    }
+
+    fn entries(&self) -> Vec<(Vec<u8>, usize)> {
+        self.iter().map(|(key, value)| (key, *value)).collect()
+    }
+}
+
+#[allow(dead_code)]
+pub fn run_testcase<K, M>(actions: Vec<Action<K>>, trie: &mut M)
+where
+    K: Clone + Ord + Hash + Debug,
+    M: FuzzMap<K>,
+{
+    let mut truth: HashMap<K, usize> = HashMap::new();
+    for action in actions {
+        match action {
+            Action::Insert(key, param) => {
+                check_same!(trie, truth, "insert", insert(key.clone(), param));
+            }
+            Action::Get(key) => {
+                check_same!(trie, truth, "get_ref", get(&key));
+            }
+            Action::ContainsKey(key) => {
+                check_same!(trie, truth, "contains_key", contains_key(&key));
+            }
+            Action::Remove(key) => {
+                check_same!(trie, truth, "remove_ref", remove(&key));
+            }
+            Action::RemoveEntry(key) => {
+                check_same!(trie, truth, "remove_entry", remove_entry(&key));
+            }
+        }
+        check_same!(trie, truth, "len", len());
+    }
+
+    let mut iter_truth = HashMap::new();
+    let mut last_key: Option<K> = None;
+    for (key, value) in trie.entries() {
+        if let Some(last_key) = last_key.replace(key.clone()) {
+            if last_key >= key {
+                panic!("iterator out of order: {:?} / {:?}", last_key, key);
+            }
+        }
+        if let Some(old_value) = iter_truth.insert(key.clone(), value) {
+            panic!(
+                "iterator gave duplicate key: {:?} / {} / {}",
+                key, value, old_value
+            );
+        }
+    }
+    if !iter_truth.eq(&truth) {
+        panic!("iterators not equal: {:?} / {:?}", iter_truth, truth);
+    }
+}
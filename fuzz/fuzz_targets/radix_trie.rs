@@ -2,34 +2,20 @@
 
 extern crate arbitrary;
 
-use std::collections::HashMap;
+mod harness;
 
-use arbitrary::{Arbitrary, Unstructured};
+use arbitrary::Unstructured;
 use libfuzzer_sys::fuzz_target;
+#[cfg(not(feature = "print_test_body"))]
 use rusty_trees::radix_trie::RadixTrie;
 
-macro_rules! check_same {
-    ($trie:ident, $truth:ident, $name:expr, $($lambda:tt)*) => {
-        let a = $trie.$($lambda)*;
-        let b = $truth.$($lambda)*;
-        if a != b {
-            panic!("method {} failed: {:?} != {:?}", $name, a, b);
-        }
-    };
-}
-
-#[derive(Arbitrary)]
-enum Action {
-    Insert(String, usize),
-    Get(String),
-    Remove(String),
-}
+use harness::Action;
 
 static mut PRINTED: bool = false;
 
 fuzz_target!(|data: &[u8]| {
     let mut source = Unstructured::new(data);
-    if let Ok(actions) = source.arbitrary::<Vec<Action>>() {
+    if let Ok(actions) = source.arbitrary::<Vec<Action<String>>>() {
         #[cfg(feature = "print_test_body")]
         {
             if unsafe { !PRINTED } {
@@ -40,15 +26,14 @@ fuzz_target!(|data: &[u8]| {
 
         #[cfg(not(feature = "print_test_body"))]
         {
-            let mut truth: HashMap<String, usize> = HashMap::new();
-            let mut trie = RadixTrie::new();
-            run_testcase(actions, &mut trie, &mut truth);
+            let mut trie: RadixTrie<String, usize> = RadixTrie::new();
+            harness::run_testcase(actions, &mut trie);
         }
     }
 });
 
 #[allow(dead_code)]
-fn print_test_body(actions: Vec<Action>) {
+fn print_test_body(actions: Vec<Action<String>>) {
     let print_assert_eq = |expected: String, actual: String| {
         println!("\tassert_eq!(\n\t\t{}, \n\t\t{}\n\t);", expected, actual);
     };
@@ -72,49 +57,28 @@ fn print_test_body(actions: Vec<Action>) {
                     format!("trie.get({:?})", key),
                 );
             }
+            Action::ContainsKey(key) => {
+                print_assert_eq(
+                    format!("truth.contains_key({:?})", key),
+                    format!("trie.contains_key({:?})", key),
+                );
+            }
             Action::Remove(key) => {
                 print_assert_eq(
                     format!("truth.remove({:?})", key),
                     format!("trie.remove({:?})", key),
                 );
             }
+            Action::RemoveEntry(key) => {
+                print_assert_eq(
+                    format!("truth.remove_entry({:?})", key),
+                    format!("trie.remove_entry({:?})", key),
+                );
+            }
         };
+        print_assert_eq("truth.len()".to_owned(), "trie.len()".to_owned());
         println!("");
     }
     println!("\tformat!(\"{{:?}}\", trie);");
     println!("\ttest_iterators(truth, trie);");
 }
-
-#[allow(dead_code)]
-fn run_testcase(
-    actions: Vec<Action>,
-    trie: &mut RadixTrie<String, usize>,
-    truth: &mut HashMap<String, usize>,
-) {
-    for action in actions {
-        match action {
-            Action::Insert(key, param) => {
-                check_same!(trie, truth, "insert", insert(key.clone(), param));
-            }
-            Action::Get(key) => {
-                check_same!(trie, truth, "get_ref", get(&key));
-            }
-            Action::Remove(key) => {
-                check_same!(trie, truth, "remove_ref", remove(&key));
-            }
-        }
-    }
-
-    let mut iter_truth = HashMap::new();
-    for (key, value) in trie.iter() {
-        if let Some(old_value) = iter_truth.insert(key.clone(), *value) {
-            panic!(
-                "iterator gave duplicate key: {} / {} / {}",
-                key, value, old_value
-            );
-        }
-    }
-    if !iter_truth.eq(&truth) {
-        panic!("iterators not equal: {:?} / {:?}", iter_truth, truth);
-    }
-}
@@ -0,0 +1,18 @@
+#![no_main]
+
+extern crate arbitrary;
+
+mod harness;
+
+use arbitrary::Unstructured;
+use libfuzzer_sys::fuzz_target;
+use rusty_trees::radix_trie::RadixTrie;
+
+use harness::{run_testcase, Action};
+
+fuzz_target!(|data: &[u8]| {
+    let mut source = Unstructured::new(data);
+    if let Ok(actions) = source.arbitrary::<Vec<Action<Vec<u8>>>>() {
+        run_testcase(actions, &mut RadixTrie::new());
+    }
+});
@@ -0,0 +1,71 @@
+use super::node::{Inner, Leaf, Node};
+
+/// Iterator over an [AdaptiveRadixTrie](super::AdaptiveRadixTrie), in
+/// lexicographic order of the keys
+///
+/// Yielded items are a tuple of (Vec<u8>, &V). Keys are cloned from the
+/// leaves, which store them in full.
+pub struct Iter<'a, V> {
+    // leaf to yield before continuing with `stack`
+    next_leaf: Option<&'a Leaf<V>>,
+
+    // inner nodes along the path to the last yielded leaf, along with the
+    // smallest key byte of the children not yet visited
+    stack: Vec<(&'a Inner<V>, usize)>,
+}
+
+impl<'a, V> Iter<'a, V> {
+    pub(super) fn new(root: Option<&'a Node<V>>) -> Self {
+        let mut iter = Iter {
+            next_leaf: None,
+            stack: vec![],
+        };
+        if let Some(root) = root {
+            iter.visit(root);
+        }
+        iter
+    }
+
+    /// Start visiting `node`, whose leaf or value is yielded next
+    fn visit(&mut self, node: &'a Node<V>) {
+        match node {
+            Node::Leaf(leaf) => self.next_leaf = Some(leaf),
+            Node::Inner(inner) => {
+                self.next_leaf = inner.value.as_deref();
+                self.stack.push((inner, 0));
+            }
+        }
+    }
+}
+
+impl<'a, V> Iterator for Iter<'a, V> {
+    type Item = (Vec<u8>, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(leaf) = self.next_leaf.take() {
+                return Some((leaf.key.clone(), &leaf.value));
+            }
+
+            let (inner, next_byte) = self.stack.last_mut()?;
+            match inner.children.next_from(*next_byte) {
+                Some((byte, child)) => {
+                    *next_byte = byte as usize + 1;
+                    self.visit(child);
+                }
+                None => {
+                    self.stack.pop();
+                }
+            }
+        }
+    }
+}
+
+impl<'a, V> IntoIterator for &'a super::AdaptiveRadixTrie<V> {
+    type Item = (Vec<u8>, &'a V);
+    type IntoIter = Iter<'a, V>;
+
+    fn into_iter(self) -> Iter<'a, V> {
+        self.iter()
+    }
+}
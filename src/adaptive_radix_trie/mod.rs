@@ -0,0 +1,392 @@
+use std::{borrow::Borrow, fmt::Debug};
+
+pub mod iter;
+mod node;
+
+use self::{
+    iter::Iter,
+    node::{Inner, Leaf, Node},
+};
+
+/// Implementation of an Adaptive Radix Tree, a trie over byte keys whose
+/// inner nodes switch between 4, 16, 48 and 256 child layouts as their fanout
+/// changes.
+///
+/// Compressed paths longer than a few bytes are only partly stored in the
+/// inner nodes, and lookups skip over the rest optimistically, checking the
+/// full key stored in the leaf they reach.
+///
+/// <https://db.in.tum.de/~leis/papers/ART.pdf>
+pub struct AdaptiveRadixTrie<V> {
+    root: Option<Node<V>>,
+
+    // Number of values in the trie
+    len: usize,
+}
+
+impl<V> AdaptiveRadixTrie<V> {
+    /// Create an empty trie
+    pub fn new() -> AdaptiveRadixTrie<V> {
+        AdaptiveRadixTrie { root: None, len: 0 }
+    }
+
+    /// Number of values in the trie
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Does the trie contain no values?
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Remove all values from the trie
+    pub fn clear(&mut self) {
+        self.root = None;
+        self.len = 0;
+    }
+
+    /// Get value corresponding to `key` in the trie (or `None` if it does not
+    /// exist)
+    pub fn get<Q>(&self, key: &Q) -> Option<&V>
+    where
+        Q: Borrow<[u8]> + ?Sized,
+    {
+        let key = key.borrow();
+        let mut node = self.root.as_ref()?;
+        let mut depth = 0;
+
+        loop {
+            match node {
+                Node::Leaf(leaf) => return (leaf.key == key).then_some(&leaf.value),
+                Node::Inner(inner) => {
+                    if !inner.prefix_matches(key, depth) {
+                        return None;
+                    }
+                    depth += inner.prefix_len;
+
+                    if key.len() == depth {
+                        let leaf = inner.value.as_ref()?;
+                        return (leaf.key == key).then_some(&leaf.value);
+                    }
+                    node = inner.children.get(key[depth])?;
+                    depth += 1;
+                }
+            }
+        }
+    }
+
+    /// Get a mutable reference to the value corresponding to `key` in the
+    /// trie (or `None` if it does not exist)
+    pub fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        Q: Borrow<[u8]> + ?Sized,
+    {
+        let key = key.borrow();
+        let mut node = self.root.as_mut()?;
+        let mut depth = 0;
+
+        loop {
+            match node {
+                Node::Leaf(leaf) => return (leaf.key == key).then_some(&mut leaf.value),
+                Node::Inner(inner) => {
+                    if !inner.prefix_matches(key, depth) {
+                        return None;
+                    }
+                    depth += inner.prefix_len;
+
+                    if key.len() == depth {
+                        let leaf = inner.value.as_mut()?;
+                        return (leaf.key == key).then_some(&mut leaf.value);
+                    }
+                    node = inner.children.get_mut(key[depth])?;
+                    depth += 1;
+                }
+            }
+        }
+    }
+
+    /// Does the trie contain a value for `key`?
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        Q: Borrow<[u8]> + ?Sized,
+    {
+        self.get(key).is_some()
+    }
+
+    /// Insert `value` at `key`, returning the old value if there was one
+    pub fn insert(&mut self, key: Vec<u8>, value: V) -> Option<V> {
+        let old_value = match &mut self.root {
+            Some(root) => root.insert(key, value, 0),
+            None => {
+                self.root = Some(Node::leaf(key, value));
+                None
+            }
+        };
+
+        if old_value.is_none() {
+            self.len += 1;
+        }
+        self.check_invariants();
+        old_value
+    }
+
+    /// Remove the value at `key`, returning it if there was one
+    pub fn remove<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        Q: Borrow<[u8]> + ?Sized,
+    {
+        self.remove_entry(key).map(|(_, value)| value)
+    }
+
+    /// Remove the value at `key`, returning the key and value if there was
+    /// one
+    pub fn remove_entry<Q>(&mut self, key: &Q) -> Option<(Vec<u8>, V)>
+    where
+        Q: Borrow<[u8]> + ?Sized,
+    {
+        let key = key.borrow();
+        let leaf = match self.root.as_mut()? {
+            Node::Leaf(leaf) => {
+                if leaf.key != key {
+                    return None;
+                }
+                match self.root.take() {
+                    Some(Node::Leaf(leaf)) => leaf,
+                    _ => unreachable!(),
+                }
+            }
+            Node::Inner(inner) => {
+                let leaf = inner.remove(key, 0)?;
+                self.root.as_mut().unwrap().collapse();
+                leaf
+            }
+        };
+
+        self.len -= 1;
+        self.check_invariants();
+        let Leaf { key, value } = *leaf;
+        Some((key, value))
+    }
+
+    /// Iterater over `(Vec<u8>, &V)` pairs that the trie contains, in
+    /// lexicographic order of the keys.
+    pub fn iter(&self) -> Iter<'_, V> {
+        Iter::new(self.root.as_ref())
+    }
+
+    /**
+     * Run in debug mode, and under the fuzzer harness
+     * Runtime invariants enforced on the trie structure that should always
+     * hold.
+     */
+    fn check_invariants(&self) {
+        #[cfg(debug_assertions)]
+        {
+            let count = match &self.root {
+                Some(root) => root.check_invariants(&mut vec![]),
+                None => Ok(0),
+            };
+            match count {
+                Ok(count) if count != self.len => panic!("len invariant failed"),
+                Ok(_) => {}
+                Err(invariant) => panic!("{}", invariant),
+            }
+        }
+    }
+}
+
+impl<V> Node<V> {
+    /// Check the invariants of the subtree under this node, where `path` is
+    /// the key leading to it. Returns the number of values in the subtree.
+    #[cfg(debug_assertions)]
+    fn check_invariants(&self, path: &mut Vec<u8>) -> Result<usize, &'static str> {
+        let inner = match self {
+            Node::Leaf(leaf) if leaf.key.starts_with(path) => return Ok(1),
+            Node::Leaf(_) => return Err("leaf key invariant failed"),
+            Node::Inner(inner) => inner,
+        };
+
+        // every entry in an inner node leads to a distinct key, or the node
+        // should have been collapsed into its parent
+        if inner.num_entries() < 2 {
+            return Err("collapsed inner node invariant failed");
+        }
+        inner.check_layout_invariant()?;
+
+        let path_len = path.len();
+        let leaf_key = &self.any_leaf().key;
+        if leaf_key.len() < path_len + inner.prefix_len {
+            return Err("leaf key invariant failed");
+        }
+        path.extend_from_slice(&leaf_key[path_len..path_len + inner.prefix_len]);
+        if !path[path_len..].starts_with(inner.stored_prefix()) {
+            return Err("stored prefix invariant failed");
+        }
+
+        let mut count = 0;
+        if let Some(leaf) = &inner.value {
+            if leaf.key != *path {
+                return Err("inner value key invariant failed");
+            }
+            count += 1;
+        }
+
+        let mut next_byte = 0;
+        while let Some((byte, child)) = inner.children.next_from(next_byte) {
+            path.push(byte);
+            count += child.check_invariants(path)?;
+            path.pop();
+            next_byte = byte as usize + 1;
+        }
+
+        path.truncate(path_len);
+        Ok(count)
+    }
+}
+
+impl<V> Inner<V> {
+    /// Invariant - the children use the smallest layout that holds them,
+    /// allowing for the slack left when shrinking
+    #[cfg(debug_assertions)]
+    fn check_layout_invariant(&self) -> Result<(), &'static str> {
+        use self::node::Children;
+
+        let len = self.children.len();
+        let fits = match &self.children {
+            Children::Node4(_) => len <= 4,
+            Children::Node16(_) => (4..=16).contains(&len),
+            Children::Node48(_) => (13..=48).contains(&len),
+            Children::Node256(_) => (38..=256).contains(&len),
+        };
+        if !fits {
+            return Err("node layout invariant failed");
+        }
+        Ok(())
+    }
+}
+
+impl<V> Default for AdaptiveRadixTrie<V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<V> FromIterator<(Vec<u8>, V)> for AdaptiveRadixTrie<V> {
+    fn from_iter<T: IntoIterator<Item = (Vec<u8>, V)>>(iter: T) -> Self {
+        let mut trie = AdaptiveRadixTrie::new();
+        trie.extend(iter);
+        trie
+    }
+}
+
+impl<V> Extend<(Vec<u8>, V)> for AdaptiveRadixTrie<V> {
+    fn extend<T: IntoIterator<Item = (Vec<u8>, V)>>(&mut self, iter: T) {
+        for (key, value) in iter {
+            self.insert(key, value);
+        }
+    }
+}
+
+impl<V> Debug for AdaptiveRadixTrie<V>
+where
+    V: Debug,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::BTreeMap;
+
+    use itertools::assert_equal;
+
+    use super::AdaptiveRadixTrie;
+
+    #[test]
+    fn test_works() {
+        let mut trie = AdaptiveRadixTrie::new();
+        assert_eq!(None, trie.get(b"".as_slice()));
+
+        assert_eq!(None, trie.insert(b"dog".to_vec(), 1));
+        assert_eq!(None, trie.insert(b"dots".to_vec(), 2));
+        assert_eq!(None, trie.insert(b"do".to_vec(), 3));
+        assert_eq!(None, trie.insert(b"".to_vec(), 4));
+        assert_eq!(Some(1), trie.insert(b"dog".to_vec(), 5));
+        assert_eq!(4, trie.len());
+
+        assert_eq!(Some(&5), trie.get(b"dog".as_slice()));
+        assert_eq!(Some(&3), trie.get(b"do".as_slice()));
+        assert_eq!(Some(&4), trie.get(b"".as_slice()));
+        assert_eq!(None, trie.get(b"d".as_slice()));
+        assert_eq!(None, trie.get(b"dot".as_slice()));
+        assert_eq!(None, trie.get(b"dogs".as_slice()));
+        assert!(trie.contains_key(b"dots".as_slice()));
+
+        *trie.get_mut(b"do".as_slice()).unwrap() += 10;
+        assert_eq!(Some(&13), trie.get(b"do".as_slice()));
+
+        assert_eq!(Some(13), trie.remove(b"do".as_slice()));
+        assert_eq!(None, trie.remove(b"do".as_slice()));
+        assert_eq!(
+            Some((b"dots".to_vec(), 2)),
+            trie.remove_entry(b"dots".as_slice())
+        );
+        assert_equal(vec![(b"".to_vec(), &4), (b"dog".to_vec(), &5)], trie.iter());
+
+        trie.clear();
+        assert!(trie.is_empty());
+        assert_eq!(0, trie.iter().count());
+    }
+
+    #[test]
+    fn test_long_prefixes() {
+        // compressed paths longer than the stored prefix
+        let long = |suffix: &[u8]| [b"0123456789abcdef".as_slice(), suffix].concat();
+        let mut trie = AdaptiveRadixTrie::new();
+        trie.insert(long(b"x"), 1);
+        trie.insert(long(b"y"), 2);
+
+        assert_eq!(Some(&1), trie.get(&long(b"x")));
+        // differs from the path after the stored bytes
+        assert_eq!(None, trie.get(b"01234567Xabcdefx".as_slice()));
+
+        // split the path after the stored bytes
+        trie.insert(b"0123456789Xbcdef".to_vec(), 3);
+        trie.insert(b"0123456789".to_vec(), 4);
+        assert_eq!(Some(&2), trie.get(&long(b"y")));
+        assert_eq!(Some(&3), trie.get(b"0123456789Xbcdef".as_slice()));
+        assert_eq!(Some(&4), trie.get(b"0123456789".as_slice()));
+
+        // merge the paths back together
+        assert_eq!(Some(3), trie.remove(b"0123456789Xbcdef".as_slice()));
+        assert_eq!(Some(4), trie.remove(b"0123456789".as_slice()));
+        assert_eq!(Some(1), trie.remove(&long(b"x")));
+        assert_equal(vec![(long(b"y"), &2)], trie.iter());
+    }
+
+    #[test]
+    fn test_node_layouts() {
+        // grow the root through every layout, and shrink it back
+        let mut trie = AdaptiveRadixTrie::new();
+        let mut truth = BTreeMap::new();
+        for byte in (0..=255u8).rev() {
+            trie.insert(vec![byte, byte], byte);
+            truth.insert(vec![byte, byte], byte);
+            assert_equal(truth.iter().map(|(k, v)| (k.clone(), v)), trie.iter());
+        }
+        for byte in 0..=255u8 {
+            assert_eq!(Some(&byte), trie.get(&[byte, byte]));
+            assert_eq!(None, trie.get(&[byte]));
+        }
+
+        for byte in (0..=255u8).step_by(3).chain((1..=255u8).step_by(3)) {
+            assert_eq!(Some(byte), trie.remove(&[byte, byte]));
+            truth.remove(&vec![byte, byte]);
+            assert_equal(truth.iter().map(|(k, v)| (k.clone(), v)), trie.iter());
+        }
+        assert_eq!(85, trie.len());
+    }
+}
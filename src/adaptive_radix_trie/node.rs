@@ -0,0 +1,533 @@
+use std::{array, mem};
+
+/// Number of bytes of an inner node's compressed path that are stored in the
+/// node. Longer paths are skipped over optimistically on lookup, and checked
+/// against the full key stored in a leaf.
+const MAX_PREFIX_LEN: usize = 8;
+
+pub(super) enum Node<V> {
+    Leaf(Box<Leaf<V>>),
+    Inner(Box<Inner<V>>),
+}
+
+/// Leaves store their full key, so that a lookup which skipped over part of a
+/// compressed path can be checked once it reaches a leaf
+pub(super) struct Leaf<V> {
+    pub(super) key: Vec<u8>,
+    pub(super) value: V,
+}
+
+pub(super) struct Inner<V> {
+    // length of the compressed path between the parent's edge and this
+    // node's children. only the first `MAX_PREFIX_LEN` bytes are kept in
+    // `prefix`
+    pub(super) prefix_len: usize,
+    prefix: [u8; MAX_PREFIX_LEN],
+
+    // value of the key that ends at this node, after the compressed path
+    pub(super) value: Option<Box<Leaf<V>>>,
+
+    pub(super) children: Children<V>,
+}
+
+/// Children of an inner node, indexed by the next byte of the key. The
+/// layout grows and shrinks with the number of children.
+pub(super) enum Children<V> {
+    Node4(Sorted<V, 4>),
+    Node16(Box<Sorted<V, 16>>),
+    Node48(Box<Node48<V>>),
+    Node256(Box<Node256<V>>),
+}
+
+/// Up to `N` children, with their key bytes kept sorted
+pub(super) struct Sorted<V, const N: usize> {
+    len: usize,
+    keys: [u8; N],
+    nodes: [Option<Node<V>>; N],
+}
+
+/// Up to 48 children, with a 256 entry index of key byte to slot + 1
+pub(super) struct Node48<V> {
+    len: usize,
+    index: [u8; 256],
+    nodes: [Option<Node<V>>; 48],
+}
+
+/// A slot for every key byte
+pub(super) struct Node256<V> {
+    len: usize,
+    nodes: [Option<Node<V>>; 256],
+}
+
+impl<V> Node<V> {
+    pub(super) fn leaf(key: Vec<u8>, value: V) -> Node<V> {
+        Node::Leaf(Box::new(Leaf { key, value }))
+    }
+
+    /// Any leaf below this node. All of them share the compressed paths on
+    /// the way to it.
+    pub(super) fn any_leaf(&self) -> &Leaf<V> {
+        match self {
+            Node::Leaf(leaf) => leaf,
+            Node::Inner(inner) => inner.any_leaf(),
+        }
+    }
+
+    /// Replace an inner node that is down to a single value or child with
+    /// that value or child, merging the compressed paths
+    pub(super) fn collapse(&mut self) {
+        let Node::Inner(inner) = self else {
+            return;
+        };
+        if inner.num_entries() != 1 {
+            return;
+        }
+
+        if let Some(leaf) = inner.value.take() {
+            *self = Node::Leaf(leaf);
+            return;
+        }
+
+        let (byte, child) = mem::replace(&mut inner.children, Children::new())
+            .into_entries()
+            .next()
+            .unwrap();
+        *self = match child {
+            Node::Leaf(leaf) => Node::Leaf(leaf),
+            Node::Inner(mut child) => {
+                // only the first MAX_PREFIX_LEN bytes of the merged path are
+                // kept, which all come from the known bytes of the two paths
+                let mut prefix = inner.stored_prefix().to_vec();
+                prefix.push(byte);
+                prefix.extend_from_slice(child.stored_prefix());
+                child.set_prefix(&prefix, inner.prefix_len + 1 + child.prefix_len);
+                Node::Inner(child)
+            }
+        };
+    }
+
+    /// Insert `value` at `key`, where the first `depth` bytes of `key` led
+    /// to this node. Returns the old value, if there was one.
+    pub(super) fn insert(&mut self, key: Vec<u8>, value: V, depth: usize) -> Option<V> {
+        match self {
+            Node::Leaf(leaf) => {
+                if leaf.key == key {
+                    return Some(mem::replace(&mut leaf.value, value));
+                }
+
+                // fork the leaf at the end of the common prefix
+                let common_len = common_prefix_len(&leaf.key[depth..], &key[depth..]);
+                let mut inner = Inner::new(&key[depth..depth + common_len], common_len);
+                inner.add_leaf(Node::leaf(key, value), depth + common_len);
+                let old_leaf = mem::replace(self, Node::Inner(Box::new(inner)));
+                if let Node::Inner(inner) = self {
+                    inner.add_leaf(old_leaf, depth + common_len);
+                }
+                None
+            }
+
+            Node::Inner(inner) => {
+                let mismatch = inner.prefix_mismatch(&key, depth);
+                if mismatch < inner.prefix_len {
+                    // the key diverges from the compressed path, split it
+                    let prefix = inner.full_prefix(depth);
+                    let mut parent = Inner::new(&prefix[..mismatch], mismatch);
+                    parent.add_leaf(Node::leaf(key, value), depth + mismatch);
+                    inner.set_prefix(&prefix[mismatch + 1..], inner.prefix_len - mismatch - 1);
+
+                    let old_node = mem::replace(self, Node::Inner(Box::new(parent)));
+                    if let Node::Inner(parent) = self {
+                        parent.children.insert(prefix[mismatch], old_node);
+                    }
+                    return None;
+                }
+
+                let depth = depth + inner.prefix_len;
+                if key.len() == depth {
+                    return match &mut inner.value {
+                        Some(leaf) => Some(mem::replace(&mut leaf.value, value)),
+                        None => {
+                            inner.value = Some(Box::new(Leaf { key, value }));
+                            None
+                        }
+                    };
+                }
+
+                let byte = key[depth];
+                match inner.children.get_mut(byte) {
+                    Some(child) => child.insert(key, value, depth + 1),
+                    None => {
+                        inner.children.insert(byte, Node::leaf(key, value));
+                        None
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl<V> Inner<V> {
+    /// Inner node with no children, whose compressed path is `prefix_len`
+    /// bytes long and starts with `prefix`
+    fn new(prefix: &[u8], prefix_len: usize) -> Inner<V> {
+        let mut inner = Inner {
+            prefix_len: 0,
+            prefix: [0; MAX_PREFIX_LEN],
+            value: None,
+            children: Children::new(),
+        };
+        inner.set_prefix(prefix, prefix_len);
+        inner
+    }
+
+    fn set_prefix(&mut self, prefix: &[u8], prefix_len: usize) {
+        let stored_len = prefix_len.min(MAX_PREFIX_LEN);
+        self.prefix[..stored_len].copy_from_slice(&prefix[..stored_len]);
+        self.prefix_len = prefix_len;
+    }
+
+    /// The bytes of the compressed path that are stored in the node
+    pub(super) fn stored_prefix(&self) -> &[u8] {
+        &self.prefix[..self.prefix_len.min(MAX_PREFIX_LEN)]
+    }
+
+    /// The whole compressed path, where `depth` bytes of key lead to this
+    /// node. Read from a leaf if it is longer than the stored bytes.
+    fn full_prefix(&self, depth: usize) -> Vec<u8> {
+        if self.prefix_len <= MAX_PREFIX_LEN {
+            return self.stored_prefix().to_vec();
+        }
+        self.any_leaf().key[depth..depth + self.prefix_len].to_vec()
+    }
+
+    /// Optimistic check that `key` follows the compressed path, comparing
+    /// only the stored bytes. A match must be confirmed against a leaf.
+    pub(super) fn prefix_matches(&self, key: &[u8], depth: usize) -> bool {
+        let stored = self.stored_prefix();
+        key.len() >= depth + self.prefix_len && key[depth..].starts_with(stored)
+    }
+
+    /// Number of bytes of the compressed path that `key` matches exactly
+    fn prefix_mismatch(&self, key: &[u8], depth: usize) -> usize {
+        let stored = self.stored_prefix();
+        let key = &key[depth..];
+        let mismatch = common_prefix_len(stored, key);
+        if mismatch < stored.len() || self.prefix_len == stored.len() {
+            return mismatch;
+        }
+
+        let leaf_key = &self.any_leaf().key[depth..depth + self.prefix_len];
+        common_prefix_len(leaf_key, key)
+    }
+
+    fn any_leaf(&self) -> &Leaf<V> {
+        match &self.value {
+            Some(leaf) => leaf,
+            None => self.children.next_from(0).unwrap().1.any_leaf(),
+        }
+    }
+
+    /// Number of children, plus the value at this node
+    pub(super) fn num_entries(&self) -> usize {
+        self.children.len() + self.value.is_some() as usize
+    }
+
+    /// Add a leaf (as a node) whose key ends at, or continues past, `depth`
+    fn add_leaf(&mut self, node: Node<V>, depth: usize) {
+        let Node::Leaf(leaf) = node else {
+            unreachable!("add_leaf called with an inner node");
+        };
+        match leaf.key.get(depth) {
+            Some(&byte) => self.children.insert(byte, Node::Leaf(leaf)),
+            None => self.value = Some(leaf),
+        }
+    }
+
+    /// Remove the value at `key`, where the first `depth` bytes of `key` led
+    /// to this node. Child nodes left with a single entry are collapsed, but
+    /// this node is left for the caller to collapse.
+    pub(super) fn remove(&mut self, key: &[u8], depth: usize) -> Option<Box<Leaf<V>>> {
+        if !self.prefix_matches(key, depth) {
+            return None;
+        }
+
+        let depth = depth + self.prefix_len;
+        if key.len() == depth {
+            return match &self.value {
+                Some(leaf) if leaf.key == key => self.value.take(),
+                _ => None,
+            };
+        }
+
+        let byte = key[depth];
+        let child = self.children.get_mut(byte)?;
+        match child {
+            Node::Leaf(leaf) => {
+                if leaf.key != key {
+                    return None;
+                }
+                match self.children.remove(byte) {
+                    Some(Node::Leaf(leaf)) => Some(leaf),
+                    _ => unreachable!(),
+                }
+            }
+            Node::Inner(inner) => {
+                let removed = inner.remove(key, depth + 1)?;
+                child.collapse();
+                Some(removed)
+            }
+        }
+    }
+}
+
+impl<V> Children<V> {
+    fn new() -> Children<V> {
+        Children::Node4(Sorted::new())
+    }
+
+    pub(super) fn len(&self) -> usize {
+        match self {
+            Children::Node4(node) => node.len,
+            Children::Node16(node) => node.len,
+            Children::Node48(node) => node.len,
+            Children::Node256(node) => node.len,
+        }
+    }
+
+    pub(super) fn get(&self, byte: u8) -> Option<&Node<V>> {
+        match self {
+            Children::Node4(node) => node.get(byte),
+            Children::Node16(node) => node.get(byte),
+            Children::Node48(node) => node.get(byte),
+            Children::Node256(node) => node.nodes[byte as usize].as_ref(),
+        }
+    }
+
+    pub(super) fn get_mut(&mut self, byte: u8) -> Option<&mut Node<V>> {
+        match self {
+            Children::Node4(node) => node.get_mut(byte),
+            Children::Node16(node) => node.get_mut(byte),
+            Children::Node48(node) => node.get_mut(byte),
+            Children::Node256(node) => node.nodes[byte as usize].as_mut(),
+        }
+    }
+
+    /// The child with the smallest key byte that is at least `byte`
+    pub(super) fn next_from(&self, byte: usize) -> Option<(u8, &Node<V>)> {
+        match self {
+            Children::Node4(node) => node.next_from(byte),
+            Children::Node16(node) => node.next_from(byte),
+            Children::Node48(node) => {
+                (byte..256).find_map(|byte| node.get(byte as u8).map(|child| (byte as u8, child)))
+            }
+            Children::Node256(node) => (byte..256)
+                .find_map(|byte| node.nodes[byte].as_ref().map(|child| (byte as u8, child))),
+        }
+    }
+
+    /// Add a child at `byte`, which must not have one yet. Grows the node to
+    /// the next layout if it is full.
+    pub(super) fn insert(&mut self, byte: u8, child: Node<V>) {
+        let full = match self {
+            Children::Node4(node) => node.len == 4,
+            Children::Node16(node) => node.len == 16,
+            Children::Node48(node) => node.len == 48,
+            Children::Node256(_) => false,
+        };
+        if full {
+            self.resize_to(self.len() + 1);
+        }
+
+        match self {
+            Children::Node4(node) => node.insert(byte, child),
+            Children::Node16(node) => node.insert(byte, child),
+            Children::Node48(node) => node.insert(byte, child),
+            Children::Node256(node) => {
+                node.nodes[byte as usize] = Some(child);
+                node.len += 1;
+            }
+        }
+    }
+
+    /// Remove the child at `byte`. Shrinks the node to a smaller layout once
+    /// it is well below capacity, so that alternating inserts and removes at
+    /// a boundary do not resize every time.
+    pub(super) fn remove(&mut self, byte: u8) -> Option<Node<V>> {
+        let child = self.take(byte)?;
+
+        let shrink = match self {
+            Children::Node4(_) => false,
+            Children::Node16(node) => node.len <= 3,
+            Children::Node48(node) => node.len <= 12,
+            Children::Node256(node) => node.len <= 37,
+        };
+        if shrink {
+            self.resize_to(self.len());
+        }
+        Some(child)
+    }
+
+    /// Remove the child at `byte`, keeping the layout
+    fn take(&mut self, byte: u8) -> Option<Node<V>> {
+        match self {
+            Children::Node4(node) => node.remove(byte),
+            Children::Node16(node) => node.remove(byte),
+            Children::Node48(node) => node.remove(byte),
+            Children::Node256(node) => {
+                let child = node.nodes[byte as usize].take();
+                node.len -= child.is_some() as usize;
+                child
+            }
+        }
+    }
+
+    /// Move the children into the smallest layout that holds `len` children
+    fn resize_to(&mut self, len: usize) {
+        let entries = mem::replace(self, Children::new()).into_entries();
+        *self = match len {
+            0..=4 => Children::Node4(Sorted::from_entries(entries)),
+            5..=16 => Children::Node16(Box::new(Sorted::from_entries(entries))),
+            17..=48 => Children::Node48(Box::new(Node48::from_entries(entries))),
+            _ => Children::Node256(Box::new(Node256::from_entries(entries))),
+        };
+    }
+
+    /// The children and their key bytes, in increasing order of key byte
+    pub(super) fn into_entries(self) -> IntoEntries<V> {
+        IntoEntries(self)
+    }
+}
+
+/// Owning iterator over children, in increasing order of key byte
+pub(super) struct IntoEntries<V>(Children<V>);
+
+impl<V> Iterator for IntoEntries<V> {
+    type Item = (u8, Node<V>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (byte, _) = self.0.next_from(0)?;
+        let child = self.0.take(byte)?;
+        Some((byte, child))
+    }
+}
+
+impl<V, const N: usize> Sorted<V, N> {
+    fn new() -> Self {
+        Sorted {
+            len: 0,
+            keys: [0; N],
+            nodes: array::from_fn(|_| None),
+        }
+    }
+
+    fn from_entries(entries: impl Iterator<Item = (u8, Node<V>)>) -> Self {
+        let mut node = Self::new();
+        for (byte, child) in entries {
+            node.keys[node.len] = byte;
+            node.nodes[node.len] = Some(child);
+            node.len += 1;
+        }
+        node
+    }
+
+    fn position(&self, byte: u8) -> Result<usize, usize> {
+        self.keys[..self.len].binary_search(&byte)
+    }
+
+    fn get(&self, byte: u8) -> Option<&Node<V>> {
+        let idx = self.position(byte).ok()?;
+        self.nodes[idx].as_ref()
+    }
+
+    fn get_mut(&mut self, byte: u8) -> Option<&mut Node<V>> {
+        let idx = self.position(byte).ok()?;
+        self.nodes[idx].as_mut()
+    }
+
+    fn next_from(&self, byte: usize) -> Option<(u8, &Node<V>)> {
+        let idx = self.keys[..self.len].partition_point(|&key| (key as usize) < byte);
+        let child = self.nodes.get(idx)?.as_ref()?;
+        Some((self.keys[idx], child))
+    }
+
+    fn insert(&mut self, byte: u8, child: Node<V>) {
+        let idx = self.position(byte).unwrap_err();
+        self.keys[idx..=self.len].rotate_right(1);
+        self.nodes[idx..=self.len].rotate_right(1);
+        self.keys[idx] = byte;
+        self.nodes[idx] = Some(child);
+        self.len += 1;
+    }
+
+    fn remove(&mut self, byte: u8) -> Option<Node<V>> {
+        let idx = self.position(byte).ok()?;
+        let child = self.nodes[idx].take();
+        self.keys[idx..self.len].rotate_left(1);
+        self.nodes[idx..self.len].rotate_left(1);
+        self.len -= 1;
+        child
+    }
+}
+
+impl<V> Node48<V> {
+    fn from_entries(entries: impl Iterator<Item = (u8, Node<V>)>) -> Self {
+        let mut node = Node48 {
+            len: 0,
+            index: [0; 256],
+            nodes: array::from_fn(|_| None),
+        };
+        for (byte, child) in entries {
+            node.insert(byte, child);
+        }
+        node
+    }
+
+    fn get(&self, byte: u8) -> Option<&Node<V>> {
+        match self.index[byte as usize] {
+            0 => None,
+            slot => self.nodes[slot as usize - 1].as_ref(),
+        }
+    }
+
+    fn get_mut(&mut self, byte: u8) -> Option<&mut Node<V>> {
+        match self.index[byte as usize] {
+            0 => None,
+            slot => self.nodes[slot as usize - 1].as_mut(),
+        }
+    }
+
+    fn insert(&mut self, byte: u8, child: Node<V>) {
+        let slot = self.nodes.iter().position(Option::is_none).unwrap();
+        self.nodes[slot] = Some(child);
+        self.index[byte as usize] = slot as u8 + 1;
+        self.len += 1;
+    }
+
+    fn remove(&mut self, byte: u8) -> Option<Node<V>> {
+        match mem::replace(&mut self.index[byte as usize], 0) {
+            0 => None,
+            slot => {
+                self.len -= 1;
+                self.nodes[slot as usize - 1].take()
+            }
+        }
+    }
+}
+
+impl<V> Node256<V> {
+    fn from_entries(entries: impl Iterator<Item = (u8, Node<V>)>) -> Self {
+        let mut node = Node256 {
+            len: 0,
+            nodes: array::from_fn(|_| None),
+        };
+        for (byte, child) in entries {
+            node.nodes[byte as usize] = Some(child);
+            node.len += 1;
+        }
+        node
+    }
+}
+
+fn common_prefix_len(a: &[u8], b: &[u8]) -> usize {
+    a.iter().zip(b).take_while(|(a, b)| a == b).count()
+}
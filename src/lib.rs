@@ -1 +1,3 @@
+pub mod adaptive_radix_trie;
+pub mod radix_set;
 pub mod radix_trie;
@@ -0,0 +1,285 @@
+use std::{borrow::Borrow, fmt::Debug};
+
+use crate::radix_trie::{self, into_iter::IntoKeys, Key, RadixTrie};
+
+/// Set of keys, stored in a [RadixTrie] with `()` values
+pub struct RadixSet<P> {
+    trie: RadixTrie<P, ()>,
+}
+
+impl<P> RadixSet<P> {
+    /// Create an empty set
+    pub fn new() -> RadixSet<P> {
+        RadixSet {
+            trie: RadixTrie::new(),
+        }
+    }
+
+    /// Number of keys in the set
+    pub fn len(&self) -> usize {
+        self.trie.len()
+    }
+
+    /// Does the set contain no keys?
+    pub fn is_empty(&self) -> bool {
+        self.trie.is_empty()
+    }
+
+    /// Remove all keys from the set
+    pub fn clear(&mut self) {
+        self.trie.clear();
+    }
+}
+
+impl<P> Default for RadixSet<P> {
+    fn default() -> Self {
+        RadixSet::new()
+    }
+}
+
+impl<P> RadixSet<P>
+where
+    P: Key,
+{
+    /// Add `key` to the set. Returns whether it was not already in the set.
+    pub fn insert(&mut self, key: P) -> bool {
+        self.trie.insert(key, ()).is_none()
+    }
+
+    /// Does the set contain `key`?
+    pub fn contains<Q>(&self, key: &Q) -> bool
+    where
+        Q: Borrow<P::Ref> + ?Sized,
+    {
+        self.trie.contains_key(key)
+    }
+
+    /// Remove `key` from the set. Returns whether it was in the set.
+    pub fn remove<Q>(&mut self, key: &Q) -> bool
+    where
+        Q: Borrow<P::Ref> + ?Sized,
+    {
+        self.trie.remove(key).is_some()
+    }
+
+    /// Iterate over the keys in the set, in lexicographic order
+    pub fn iter(&self) -> Iter<'_, P> {
+        Iter(self.trie.iter())
+    }
+
+    /// Iterate over the keys in the set that start with `prefix`, in
+    /// lexicographic order
+    pub fn iter_prefix<Q>(&self, prefix: &Q) -> Iter<'_, P>
+    where
+        Q: Borrow<P::Ref> + ?Sized,
+    {
+        Iter(self.trie.iter_prefix(prefix))
+    }
+
+    /// Get the longest key in the set that is a prefix of `key`, borrowed
+    /// from `key`
+    pub fn longest_prefix<'a, Q>(&'a self, key: &'a Q) -> Option<&'a P::Ref>
+    where
+        Q: Borrow<P::Ref> + ?Sized,
+    {
+        self.trie.longest_prefix(key).map(|(prefix, _)| prefix)
+    }
+
+    /// Is every key in the set also in `other`?
+    pub fn is_subset(&self, other: &RadixSet<P>) -> bool {
+        self.len() <= other.len() && self.all_keys(|key| other.contains(key))
+    }
+
+    /// Is every key in `other` also in the set?
+    pub fn is_superset(&self, other: &RadixSet<P>) -> bool {
+        other.is_subset(self)
+    }
+
+    /// Do the set and `other` have no keys in common?
+    pub fn is_disjoint(&self, other: &RadixSet<P>) -> bool {
+        // look up the keys of the smaller set in the larger one
+        let (smaller, larger) = if self.len() <= other.len() {
+            (self, other)
+        } else {
+            (other, self)
+        };
+        smaller.all_keys(|key| !larger.contains(key))
+    }
+
+    /// Is `f` true for every key in the set? Keys are borrowed from the
+    /// iterator, so none are allocated.
+    fn all_keys<F>(&self, mut f: F) -> bool
+    where
+        F: FnMut(&P::Ref) -> bool,
+    {
+        let mut iter = self.trie.iter();
+        while let Some((key, _)) = iter.next_with_key() {
+            if !f(key) {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+/// Iterator over the keys of a [RadixSet], in lexicographic order
+pub struct Iter<'a, P>(radix_trie::iter::Iter<'a, P, ()>)
+where
+    P: Key;
+
+impl<'a, P> Iterator for Iter<'a, P>
+where
+    P: Key,
+{
+    type Item = P;
+
+    fn next(&mut self) -> Option<P> {
+        self.0.next().map(|(key, _)| key)
+    }
+}
+
+impl<'a, P> DoubleEndedIterator for Iter<'a, P>
+where
+    P: Key,
+{
+    fn next_back(&mut self) -> Option<P> {
+        self.0.next_back().map(|(key, _)| key)
+    }
+}
+
+impl<'a, P> IntoIterator for &'a RadixSet<P>
+where
+    P: Key,
+{
+    type Item = P;
+    type IntoIter = Iter<'a, P>;
+
+    fn into_iter(self) -> Iter<'a, P> {
+        self.iter()
+    }
+}
+
+impl<P> IntoIterator for RadixSet<P>
+where
+    P: Key,
+{
+    type Item = P;
+    type IntoIter = IntoKeys<P, ()>;
+
+    fn into_iter(self) -> IntoKeys<P, ()> {
+        self.trie.into_keys()
+    }
+}
+
+impl<P> FromIterator<P> for RadixSet<P>
+where
+    P: Key,
+{
+    fn from_iter<T: IntoIterator<Item = P>>(iter: T) -> Self {
+        let mut set = RadixSet::new();
+        set.extend(iter);
+        set
+    }
+}
+
+impl<P> Extend<P> for RadixSet<P>
+where
+    P: Key,
+{
+    fn extend<T: IntoIterator<Item = P>>(&mut self, iter: T) {
+        self.trie.extend(iter.into_iter().map(|key| (key, ())));
+    }
+}
+
+impl<P> PartialEq for RadixSet<P>
+where
+    P: Key,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len() && self.is_subset(other)
+    }
+}
+
+impl<P> Eq for RadixSet<P> where P: Key {}
+
+impl<P> Debug for RadixSet<P>
+where
+    P: Key + Debug,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::path::PathBuf;
+
+    use itertools::assert_equal;
+
+    use super::RadixSet;
+
+    #[test]
+    fn test_works() {
+        let mut set = RadixSet::<String>::new();
+        assert!(set.is_empty());
+        assert!(set.insert("dog".into()));
+        assert!(set.insert("do".into()));
+        assert!(!set.insert("dog".into()));
+        assert!(set.insert("".into()));
+        assert_eq!(3, set.len());
+
+        assert!(set.contains("do"));
+        assert!(set.contains(""));
+        assert!(!set.contains("d"));
+
+        assert!(set.remove("do"));
+        assert!(!set.remove("do"));
+        assert!(!set.contains("do"));
+        assert_eq!(2, set.len());
+
+        set.clear();
+        assert!(set.is_empty());
+        assert!(!set.contains("dog"));
+    }
+
+    #[test]
+    fn test_iter() {
+        let set: RadixSet<String> = ["dots", "b", "dog", "a", "do"]
+            .into_iter()
+            .map(String::from)
+            .collect();
+
+        assert_equal(vec!["a", "b", "do", "dog", "dots"], set.iter());
+        assert_equal(vec!["dots", "dog", "do", "b", "a"], set.iter().rev());
+        assert_equal(vec!["do", "dog", "dots"], set.iter_prefix("d"));
+        assert_equal(vec!["dots"], set.iter_prefix("dot"));
+        assert_eq!(Some("dog"), set.longest_prefix("doggy"));
+        assert_eq!(None, set.longest_prefix("c"));
+        assert_equal(set.iter(), &set);
+        assert_equal(vec!["a", "b", "do", "dog", "dots"], set);
+    }
+
+    #[test]
+    fn test_subset_and_disjoint() {
+        let set = |keys: &[&str]| -> RadixSet<PathBuf> { keys.iter().map(PathBuf::from).collect() };
+        let all = set(&["/a", "/a/b", "/c", "/c/d/e"]);
+        let some = set(&["/a/b", "/c/d/e"]);
+        let other = set(&["/a/c", "/c/d"]);
+
+        assert!(some.is_subset(&all));
+        assert!(all.is_superset(&some));
+        assert!(!all.is_subset(&some));
+        assert!(!other.is_subset(&all));
+        assert!(RadixSet::new().is_subset(&some));
+
+        assert!(some.is_disjoint(&other));
+        assert!(other.is_disjoint(&all));
+        assert!(!some.is_disjoint(&all));
+        assert!(RadixSet::new().is_disjoint(&all));
+
+        assert_eq!(some, set(&["/c/d/e", "/a/b"]));
+        assert_ne!(some, other);
+        assert_eq!(r#"{"/a/b", "/c/d/e"}"#, format!("{:?}", some));
+    }
+}
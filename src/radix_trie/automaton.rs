@@ -0,0 +1,279 @@
+use std::iter;
+
+use crate::radix_trie::{Key, KeyRef, Node, Trie};
+
+/// Automaton that keys are run through, a unit at a time (e.g. a DFA for a
+/// regex). `R` is the reference type of the key, and each unit is passed as
+/// an `&R` of a single unit (for `str` keys, the `&str` of one char).
+///
+/// [RadixTrie::search](super::RadixTrie::search) runs the automaton along
+/// the edges of the trie, so the units of a shared prefix are stepped
+/// through once.
+pub trait Automaton<R: ?Sized> {
+    /// State of the automaton after some units of a key
+    type State: Clone;
+
+    /// State before any unit
+    fn start(&self) -> Self::State;
+
+    /// State after `unit`, from `state`
+    fn step(&self, state: &Self::State, unit: &R) -> Self::State;
+
+    /// Does a key that ends in `state` match?
+    fn is_match(&self, state: &Self::State) -> bool;
+
+    /// Can a key that continues on from `state` match? When `false`, no key
+    /// below the node the state is in is visited.
+    fn can_match(&self, state: &Self::State) -> bool;
+}
+
+impl<R, A> Automaton<R> for &A
+where
+    R: ?Sized,
+    A: Automaton<R> + ?Sized,
+{
+    type State = A::State;
+
+    fn start(&self) -> A::State {
+        (**self).start()
+    }
+
+    fn step(&self, state: &A::State, unit: &R) -> A::State {
+        (**self).step(state, unit)
+    }
+
+    fn is_match(&self, state: &A::State) -> bool {
+        (**self).is_match(state)
+    }
+
+    fn can_match(&self, state: &A::State) -> bool {
+        (**self).can_match(state)
+    }
+}
+
+/// Iterator over the entries of a [RadixTrie](super::RadixTrie) whose key
+/// an [Automaton] matches. Created by
+/// [RadixTrie::search](super::RadixTrie::search).
+///
+/// Yielded items are a tuple of (P, &V), in lexicographic order of the keys.
+pub struct Search<'a, P, V, A>
+where
+    P: Key,
+    A: Automaton<P::Ref>,
+{
+    automaton: A,
+    // value of the root, if the empty key matches
+    root_value: Option<&'a V>,
+    // tries being visited, from which the automaton can still match
+    stack: Vec<SearchState<'a, P, V, A::State>>,
+    key: P,
+}
+
+struct SearchState<'a, P, V, S>
+where
+    P: Key,
+{
+    // length of the key of the trie, in `Search::key`
+    key_len: usize,
+    // state of the automaton after the key of the trie
+    state: S,
+    nodes: &'a [Node<P, V>],
+}
+
+impl<'a, P, V, A> Search<'a, P, V, A>
+where
+    P: Key,
+    A: Automaton<P::Ref>,
+{
+    pub(super) fn new(tree: &'a Trie<P, V>, automaton: A) -> Self {
+        let state = automaton.start();
+        let mut stack = vec![];
+        let mut root_value = None;
+        if automaton.can_match(&state) {
+            root_value = tree.value.as_ref().filter(|_| automaton.is_match(&state));
+            stack.push(SearchState {
+                key_len: 0,
+                state,
+                nodes: &tree.nodes[..],
+            });
+        }
+
+        Search {
+            automaton,
+            root_value,
+            stack,
+            key: P::Ref::concat(&mut iter::empty()),
+        }
+    }
+}
+
+impl<'a, P, V, A> Iterator for Search<'a, P, V, A>
+where
+    P: Key,
+    A: Automaton<P::Ref>,
+{
+    type Item = (P, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(value) = self.root_value.take() {
+            return Some((self.key.borrow().to_owned(), value));
+        }
+
+        'nodes: loop {
+            let search_state = self.stack.last_mut()?;
+            let Some((node, rest)) = search_state.nodes.split_first() else {
+                self.stack.pop();
+                continue;
+            };
+            search_state.nodes = rest;
+            let key_len = search_state.key_len;
+            let mut state = search_state.state.clone();
+
+            // skip the node as soon as the automaton can't match
+            let mut edge = node.key.borrow();
+            while let Some((unit, edge_rest)) = edge.split_first() {
+                state = self.automaton.step(&state, unit);
+                if !self.automaton.can_match(&state) {
+                    continue 'nodes;
+                }
+                edge = edge_rest;
+            }
+
+            self.key.truncate(key_len);
+            self.key.append_ref(node.key.borrow());
+            let is_match = self.automaton.is_match(&state);
+            self.stack.push(SearchState {
+                key_len: self.key.borrow().len(),
+                state,
+                nodes: &node.trie.nodes[..],
+            });
+
+            if let Some(value) = node.trie.value.as_ref().filter(|_| is_match) {
+                return Some((self.key.borrow().to_owned(), value));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::cell::Cell;
+
+    use itertools::assert_equal;
+
+    use crate::radix_trie::RadixTrie;
+
+    use super::Automaton;
+
+    /// Matches keys starting with a prefix, counting the units it steps
+    /// through
+    struct StartsWith<'a> {
+        prefix: Vec<char>,
+        steps: &'a Cell<usize>,
+    }
+
+    impl Automaton<str> for StartsWith<'_> {
+        // chars of the prefix matched so far, or `None` after a mismatch
+        type State = Option<usize>;
+
+        fn start(&self) -> Option<usize> {
+            Some(0)
+        }
+
+        fn step(&self, state: &Option<usize>, unit: &str) -> Option<usize> {
+            self.steps.set(self.steps.get() + 1);
+            let matched = (*state)?;
+            match self.prefix.get(matched) {
+                None => Some(matched),
+                Some(&c) if unit.starts_with(c) => Some(matched + 1),
+                Some(_) => None,
+            }
+        }
+
+        fn is_match(&self, state: &Option<usize>) -> bool {
+            *state == Some(self.prefix.len())
+        }
+
+        fn can_match(&self, state: &Option<usize>) -> bool {
+            state.is_some()
+        }
+    }
+
+    /// Matches byte keys with an even sum, of at most `max_len` bytes
+    struct EvenSum {
+        max_len: usize,
+    }
+
+    impl Automaton<[u8]> for EvenSum {
+        // (length, sum) of the bytes so far
+        type State = (usize, u8);
+
+        fn start(&self) -> (usize, u8) {
+            (0, 0)
+        }
+
+        fn step(&self, &(len, sum): &(usize, u8), unit: &[u8]) -> (usize, u8) {
+            (len + 1, sum.wrapping_add(unit[0]))
+        }
+
+        fn is_match(&self, &(_, sum): &(usize, u8)) -> bool {
+            sum % 2 == 0
+        }
+
+        fn can_match(&self, &(len, _): &(usize, u8)) -> bool {
+            len <= self.max_len
+        }
+    }
+
+    #[test]
+    fn test_works() {
+        let trie: RadixTrie<String, usize> =
+            ["", "app", "apple", "apply", "banana", "bandana", "Юa"]
+                .into_iter()
+                .enumerate()
+                .map(|(idx, key)| (key.to_owned(), idx))
+                .collect();
+
+        let steps = Cell::new(0);
+        let starts_with = |prefix: &str| StartsWith {
+            prefix: prefix.chars().collect(),
+            steps: &steps,
+        };
+
+        assert_equal(
+            vec![
+                ("app".into(), &1),
+                ("apple".into(), &2),
+                ("apply".into(), &3),
+            ],
+            trie.search(starts_with("app")),
+        );
+        assert_equal(
+            vec![("banana".into(), &4), ("bandana".into(), &5)],
+            trie.search(starts_with("ban")),
+        );
+        assert_equal(vec![("Юa".into(), &6)], trie.search(&starts_with("Ю")));
+        assert_eq!(trie.len(), trie.search(starts_with("")).count());
+
+        // nodes are skipped as soon as the automaton can't match
+        steps.set(0);
+        assert_eq!(0, trie.search(starts_with("c")).count());
+        assert_eq!(3, steps.get());
+    }
+
+    #[test]
+    fn test_bytes() {
+        let trie: RadixTrie<Vec<u8>, ()> =
+            [&[][..], &[1], &[1, 1], &[1, 2], &[2, 2, 2], &[2, 2, 2, 2]]
+                .into_iter()
+                .map(|key| (key.to_vec(), ()))
+                .collect();
+
+        assert_eq!(
+            vec![vec![], vec![1, 1], vec![2, 2, 2]],
+            trie.search(EvenSum { max_len: 3 })
+                .map(|(key, _)| key)
+                .collect::<Vec<_>>()
+        );
+    }
+}
@@ -0,0 +1,248 @@
+use std::{cmp::Ordering, error::Error, fmt};
+
+use crate::radix_trie::{Key, KeyRef, Node, RadixTrie, Trie};
+
+/// Builds a [RadixTrie] in a single pass over keys in increasing order,
+/// without searching for or splitting existing nodes as
+/// [RadixTrie::insert] does.
+///
+/// Only the nodes along the path of the last pushed key are kept open. When
+/// a key is pushed, the nodes it does not share a prefix with can never gain
+/// another child, so they are closed and attached to their parent.
+pub struct RadixTrieBuilder<P, V> {
+    // nodes along the path of the last pushed key, starting with the root
+    stack: Vec<BuilderFrame<P, V>>,
+    last_key: Option<P>,
+    len: usize,
+}
+
+struct BuilderFrame<P, V> {
+    // `None` for the root trie
+    key: Option<P>,
+    // length of the full key up to the start of `key`
+    start: usize,
+    trie: Trie<P, V>,
+}
+
+/// Error returned by [RadixTrieBuilder::push] when a key is not greater than
+/// the previously pushed key. Holds the rejected key and value.
+#[derive(Debug)]
+pub struct UnsortedKeyError<P, V> {
+    pub key: P,
+    pub value: V,
+}
+
+impl<P, V> RadixTrieBuilder<P, V>
+where
+    P: Key,
+{
+    /// Create a builder for an empty trie
+    pub fn new() -> RadixTrieBuilder<P, V> {
+        RadixTrieBuilder {
+            stack: vec![BuilderFrame {
+                key: None,
+                start: 0,
+                trie: Trie::new(),
+            }],
+            last_key: None,
+            len: 0,
+        }
+    }
+
+    /// Add `value` at `key` to the trie. `key` must be strictly greater than
+    /// the previously pushed key (in the order that [RadixTrie::iter] yields
+    /// keys), otherwise the key and value are returned in an error.
+    pub fn push(&mut self, key: P, value: V) -> Result<(), UnsortedKeyError<P, V>> {
+        let common_len = match &self.last_key {
+            None => 0,
+            Some(last_key) => {
+                let (last_key, new_key) = (last_key.borrow(), key.borrow());
+                if P::Ref::cmp_keys(last_key, new_key) != Ordering::Less {
+                    return Err(UnsortedKeyError { key, value });
+                }
+                P::Ref::prefix(last_key, new_key).0.len()
+            }
+        };
+
+        // close the nodes which begin past the common prefix of the last key
+        // and this key
+        while self.stack.len() > 1 && self.top().start >= common_len {
+            self.close_frame();
+        }
+
+        // the common prefix ends partway through the top node's key, so fork
+        // the node there. its subtree is complete, as all following keys
+        // diverge from it
+        let top = self.top();
+        let top_end = top.start + top.key.as_ref().map_or(0, |key| key.borrow().len());
+        if top_end > common_len {
+            let frame = self.stack.pop().unwrap();
+            let node_key = frame.key.unwrap();
+            let (prefix, rest) = node_key.borrow().split_at(common_len - frame.start);
+
+            let mut interior_trie = Trie::with_value_and_capacity(None, 2);
+            interior_trie.nodes.push(Node {
+                key: rest.to_owned(),
+                trie: frame.trie,
+            });
+            self.stack.push(BuilderFrame {
+                key: Some(prefix.to_owned()),
+                start: frame.start,
+                trie: interior_trie,
+            });
+        }
+
+        let (_, key_rest) = key.borrow().split_at(common_len);
+        if key_rest.is_empty() {
+            // only possible for the first key, when it is empty
+            self.stack.last_mut().unwrap().trie.value = Some(value);
+        } else {
+            self.stack.push(BuilderFrame {
+                key: Some(key_rest.to_owned()),
+                start: common_len,
+                trie: Trie::with_value(value),
+            });
+        }
+
+        self.len += 1;
+        self.last_key = Some(key);
+        Ok(())
+    }
+
+    /// Finish building the trie
+    pub fn build(mut self) -> RadixTrie<P, V> {
+        while self.stack.len() > 1 {
+            self.close_frame();
+        }
+
+        let trie = RadixTrie {
+            root: self.stack.pop().unwrap().trie,
+            len: self.len,
+        };
+        trie.check_invariants();
+        trie
+    }
+
+    fn top(&self) -> &BuilderFrame<P, V> {
+        self.stack.last().unwrap()
+    }
+
+    /// Attach the top node to its parent, which is now the top node
+    fn close_frame(&mut self) {
+        let frame = self.stack.pop().unwrap();
+        self.stack.last_mut().unwrap().trie.nodes.push(Node {
+            key: frame.key.unwrap(),
+            trie: frame.trie,
+        });
+    }
+}
+
+impl<P, V> Default for RadixTrieBuilder<P, V>
+where
+    P: Key,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<P, V> fmt::Display for UnsortedKeyError<P, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("key is not greater than the previous key (keys must be sorted and unique)")
+    }
+}
+
+impl<P, V> Error for UnsortedKeyError<P, V>
+where
+    P: fmt::Debug,
+    V: fmt::Debug,
+{
+}
+
+#[cfg(test)]
+mod test {
+    use itertools::assert_equal;
+
+    use super::RadixTrieBuilder;
+    use crate::radix_trie::RadixTrie;
+
+    fn insert_all(entries: &[(&str, usize)]) -> RadixTrie<String, usize> {
+        entries
+            .iter()
+            .map(|&(key, value)| (key.to_owned(), value))
+            .collect()
+    }
+
+    #[test]
+    fn test_works() {
+        let entries = [
+            ("", 0),
+            ("a", 1),
+            ("ab", 2),
+            ("abc", 3),
+            ("abd", 4),
+            ("b", 5),
+            ("bcd", 6),
+            ("bce", 7),
+            ("bd", 8),
+            ("Ю", 9),
+            ("Юa", 10),
+            ("Я", 11),
+        ];
+        let trie = RadixTrie::from_sorted_iter(
+            entries.iter().map(|&(key, value)| (key.to_owned(), value)),
+        );
+
+        assert_eq!(entries.len(), trie.len());
+        assert_equal(insert_all(&entries).iter(), trie.iter());
+        // same node structure as a trie built by inserting
+        assert_eq!(format!("{:?}", insert_all(&entries)), format!("{:?}", trie));
+    }
+
+    #[test]
+    fn test_builder() {
+        let trie = RadixTrieBuilder::<String, usize>::new().build();
+        assert_eq!(0, trie.len());
+
+        let mut builder = RadixTrieBuilder::new();
+        builder.push("dog".to_owned(), 1).unwrap();
+        builder.push("dots".to_owned(), 2).unwrap();
+        builder.push("dotty".to_owned(), 3).unwrap();
+        let mut trie = builder.build();
+        assert_eq!(
+            format!("{:?}", insert_all(&[("dog", 1), ("dots", 2), ("dotty", 3)])),
+            format!("{:?}", trie)
+        );
+
+        // the trie can be modified after being built
+        trie.insert("do".to_owned(), 4);
+        assert_eq!(Some(3), trie.remove("dotty"));
+        assert_eq!(Some(&2), trie.get("dots"));
+    }
+
+    #[test]
+    fn test_rejects_unsorted() {
+        let mut builder = RadixTrieBuilder::new();
+        builder.push("b".to_owned(), 1).unwrap();
+
+        let err = builder.push("a".to_owned(), 2).unwrap_err();
+        assert_eq!(("a".to_owned(), 2), (err.key, err.value));
+        // a key that is a prefix of the previous key sorts before it
+        assert!(builder.push("".to_owned(), 3).is_err());
+        // duplicate key
+        assert!(builder.push("b".to_owned(), 4).is_err());
+
+        // the builder is still usable after an error
+        builder.push("c".to_owned(), 5).unwrap();
+        assert_equal(
+            vec![("b".into(), &1), ("c".into(), &5)],
+            builder.build().iter(),
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "keys must be sorted and unique")]
+    fn test_from_sorted_iter_panics() {
+        RadixTrie::from_sorted_iter(vec![("b".to_owned(), 1), ("a".to_owned(), 2)]);
+    }
+}
@@ -0,0 +1,88 @@
+use crate::radix_trie::{Key, KeyRef, Trie};
+
+/// Iterator over the entries of a [RadixTrie](super::RadixTrie) whose key is
+/// a prefix of some query key. Created by
+/// [RadixTrie::common_prefixes](super::RadixTrie::common_prefixes).
+///
+/// Yielded items are a tuple of (&P::Ref, &V), where the key is borrowed from
+/// the query. Entries are yielded from the shortest key to the longest.
+pub struct CommonPrefixes<'a, P, V>
+where
+    P: Key,
+{
+    query: &'a P::Ref,
+    // remaining part of the query, below `trie`
+    rest: &'a P::Ref,
+    trie: Option<&'a Trie<P, V>>,
+}
+
+impl<'a, P, V> CommonPrefixes<'a, P, V>
+where
+    P: Key,
+{
+    pub(super) fn new(tree: &'a Trie<P, V>, query: &'a P::Ref) -> Self {
+        CommonPrefixes {
+            query,
+            rest: query,
+            trie: Some(tree),
+        }
+    }
+}
+
+impl<'a, P, V> Iterator for CommonPrefixes<'a, P, V>
+where
+    P: Key,
+{
+    type Item = (&'a P::Ref, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let trie = self.trie?;
+            let rest = self.rest;
+
+            match trie.child_prefix_of(rest) {
+                Some((child, child_rest)) => {
+                    self.trie = Some(child);
+                    self.rest = child_rest;
+                }
+                None => self.trie = None,
+            }
+
+            if let Some(value) = trie.value.as_ref() {
+                let (key, _) = self.query.split_at(self.query.len() - rest.len());
+                return Some((key, value));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use itertools::assert_equal;
+
+    use crate::radix_trie::RadixTrie;
+
+    #[test]
+    fn test_works() {
+        let mut trie = RadixTrie::<String, _>::new();
+        assert_eq!(0, trie.common_prefixes("abc").count());
+
+        trie.insert("a".into(), 1);
+        trie.insert("abc".into(), 2);
+        trie.insert("abcd".into(), 3);
+        trie.insert("ab".into(), 4);
+        trie.insert("b".into(), 5);
+
+        assert_equal(
+            vec![("a", &1), ("ab", &4), ("abc", &2)],
+            trie.common_prefixes("abcz"),
+        );
+        assert_equal(vec![("a", &1)], trie.common_prefixes("a"));
+        assert_equal(vec![("a", &1)], trie.common_prefixes("acd"));
+        assert_eq!(0, trie.common_prefixes("").count());
+        assert_eq!(0, trie.common_prefixes("c").count());
+
+        trie.insert("".into(), 0);
+        assert_equal(vec![("", &0), ("b", &5)], trie.common_prefixes("bb"));
+    }
+}
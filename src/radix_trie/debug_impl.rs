@@ -1,4 +1,4 @@
-use super::RadixTrie;
+use super::{RadixTrie, Trie};
 use std::fmt::Debug;
 
 impl<P, V> Debug for RadixTrie<P, V>
@@ -8,12 +8,12 @@ where
 {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.write_str("(root) ")?;
-        Self::fmt_impl(0, self, f)?;
+        Trie::fmt_impl(0, &self.root, f)?;
         Ok(())
     }
 }
 
-impl<P, V> RadixTrie<P, V>
+impl<P, V> Trie<P, V>
 where
     P: Debug,
     V: Debug,
@@ -23,7 +23,7 @@ where
         f.write_str(&format!("`{:?}`\n", trie.value))?;
         for node in &trie.nodes {
             f.write_str(&format!("{}- {:?} ", ident_str, node.key))?;
-            RadixTrie::fmt_impl(ident + 2, &node.trie, f)?
+            Trie::fmt_impl(ident + 2, &node.trie, f)?
         }
 
         Ok(())
@@ -32,18 +32,21 @@ where
 
 #[cfg(test)]
 mod test {
-    use crate::radix_trie::{Node, RadixTrie};
+    use crate::radix_trie::{Node, RadixTrie, Trie};
 
     #[test]
     fn test_can_print_debug() {
         let trie: RadixTrie<String, i32> = RadixTrie {
-            value: Some(5),
-            nodes: vec![Node {
-                key: "foo".into(),
-                trie: RadixTrie::default(),
-            }],
+            root: Trie {
+                value: Some(5),
+                nodes: vec![Node {
+                    key: "foo".into(),
+                    trie: Trie::new(),
+                }],
+            },
+            len: 1,
         };
 
-        format!("{:?}", trie);
+        let _ = format!("{:?}", trie);
     }
 }
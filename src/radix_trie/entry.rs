@@ -0,0 +1,221 @@
+use std::mem;
+
+use crate::radix_trie::{Key, VacantSlot};
+
+/// A view into a single entry of a [RadixTrie](super::RadixTrie), which may
+/// either be vacant or occupied. Created by
+/// [RadixTrie::entry](super::RadixTrie::entry).
+pub enum Entry<'a, P, V> {
+    Occupied(OccupiedEntry<'a, P, V>),
+    Vacant(VacantEntry<'a, P, V>),
+}
+
+/// An entry of the trie that has a value
+pub struct OccupiedEntry<'a, P, V> {
+    key: P,
+    value: &'a mut V,
+}
+
+/// An entry of the trie that does not have a value. The place in the trie
+/// where the value will go has already been found, so inserting into it does
+/// not walk the trie again.
+pub struct VacantEntry<'a, P, V> {
+    key: P,
+    slot: VacantSlot<'a, P, V>,
+    // number of values in the trie, incremented on insert
+    len: &'a mut usize,
+}
+
+impl<'a, P, V> Entry<'a, P, V>
+where
+    P: Key,
+{
+    pub(super) fn occupied(key: P, value: &'a mut V) -> Self {
+        Entry::Occupied(OccupiedEntry { key, value })
+    }
+
+    pub(super) fn vacant(key: P, slot: VacantSlot<'a, P, V>, len: &'a mut usize) -> Self {
+        Entry::Vacant(VacantEntry { key, slot, len })
+    }
+
+    /// Key of the entry
+    pub fn key(&self) -> &P {
+        match self {
+            Entry::Occupied(entry) => entry.key(),
+            Entry::Vacant(entry) => entry.key(),
+        }
+    }
+
+    /// Insert `default` if the entry is vacant, and return a mutable
+    /// reference to the entry's value
+    pub fn or_insert(self, default: V) -> &'a mut V {
+        self.or_insert_with(|| default)
+    }
+
+    /// Insert the result of `default` if the entry is vacant, and return a
+    /// mutable reference to the entry's value
+    pub fn or_insert_with<F>(self, default: F) -> &'a mut V
+    where
+        F: FnOnce() -> V,
+    {
+        match self {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => entry.insert(default()),
+        }
+    }
+
+    /// Call `f` on the entry's value if the entry is occupied
+    pub fn and_modify<F>(self, f: F) -> Self
+    where
+        F: FnOnce(&mut V),
+    {
+        match self {
+            Entry::Occupied(entry) => {
+                f(entry.value);
+                Entry::Occupied(entry)
+            }
+            Entry::Vacant(entry) => Entry::Vacant(entry),
+        }
+    }
+}
+
+impl<'a, P, V> Entry<'a, P, V>
+where
+    P: Key,
+    V: Default,
+{
+    /// Insert `V::default()` if the entry is vacant, and return a mutable
+    /// reference to the entry's value
+    pub fn or_default(self) -> &'a mut V {
+        self.or_insert_with(V::default)
+    }
+}
+
+impl<'a, P, V> OccupiedEntry<'a, P, V>
+where
+    P: Key,
+{
+    /// Key of the entry
+    pub fn key(&self) -> &P {
+        &self.key
+    }
+
+    /// Value of the entry
+    pub fn get(&self) -> &V {
+        self.value
+    }
+
+    /// Mutable value of the entry
+    pub fn get_mut(&mut self) -> &mut V {
+        self.value
+    }
+
+    /// Convert the entry into a mutable reference to its value, with the
+    /// lifetime of the trie
+    pub fn into_mut(self) -> &'a mut V {
+        self.value
+    }
+
+    /// Replace the value of the entry, returning the old value
+    pub fn insert(&mut self, value: V) -> V {
+        mem::replace(self.value, value)
+    }
+}
+
+impl<'a, P, V> VacantEntry<'a, P, V>
+where
+    P: Key,
+{
+    /// Key of the entry
+    pub fn key(&self) -> &P {
+        &self.key
+    }
+
+    /// Take ownership of the key
+    pub fn into_key(self) -> P {
+        self.key
+    }
+
+    /// Insert `value` into the trie at the entry's key, and return a mutable
+    /// reference to it
+    pub fn insert(self, value: V) -> &'a mut V {
+        *self.len += 1;
+        self.slot.insert(self.key.borrow(), value)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use itertools::assert_equal;
+
+    use super::Entry;
+    use crate::radix_trie::RadixTrie;
+
+    #[test]
+    fn test_or_insert() {
+        let mut trie = RadixTrie::<String, usize>::new();
+        *trie.entry("dog".into()).or_insert(0) += 1;
+        *trie.entry("dog".into()).or_insert(0) += 1;
+        // splits "dog", creating a fork
+        *trie.entry("dots".into()).or_insert(0) += 1;
+        // splits "do", creating an interior value
+        *trie.entry("d".into()).or_insert_with(|| 5) += 1;
+        *trie.entry("".into()).or_default() += 3;
+        // interior node without a value
+        *trie.entry("do".into()).or_default() += 4;
+        // new child node
+        *trie.entry("cat".into()).or_default() += 7;
+
+        assert_equal(
+            vec![
+                ("".into(), &3),
+                ("cat".into(), &7),
+                ("d".into(), &6),
+                ("do".into(), &4),
+                ("dog".into(), &2),
+                ("dots".into(), &1),
+            ],
+            trie.iter(),
+        );
+    }
+
+    #[test]
+    fn test_and_modify() {
+        let mut trie = RadixTrie::<String, usize>::new();
+        trie.entry("a".into()).and_modify(|v| *v += 1).or_insert(1);
+        trie.entry("a".into()).and_modify(|v| *v += 1).or_insert(1);
+        trie.entry("ab".into())
+            .and_modify(|v| *v += 1)
+            .or_insert(10);
+        assert_eq!(Some(&2), trie.get("a"));
+        assert_eq!(Some(&10), trie.get("ab"));
+    }
+
+    #[test]
+    fn test_occupied_and_vacant() {
+        let mut trie = RadixTrie::<String, usize>::new();
+        trie.insert("abc".into(), 1);
+
+        match trie.entry("abc".into()) {
+            Entry::Occupied(mut entry) => {
+                assert_eq!("abc", entry.key());
+                assert_eq!(&1, entry.get());
+                assert_eq!(1, entry.insert(2));
+                *entry.get_mut() += 1;
+            }
+            Entry::Vacant(_) => panic!("expected occupied entry"),
+        }
+        assert_eq!(Some(&3), trie.get("abc"));
+
+        match trie.entry("ab".into()) {
+            Entry::Occupied(_) => panic!("expected vacant entry"),
+            Entry::Vacant(entry) => {
+                assert_eq!("ab", entry.key());
+                assert_eq!(&mut 4, entry.insert(4));
+            }
+        }
+        assert_eq!(Some(&4), trie.get("ab"));
+        assert_eq!(Some(&3), trie.get("abc"));
+        assert_eq!(2, trie.len());
+    }
+}
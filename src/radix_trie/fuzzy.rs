@@ -0,0 +1,235 @@
+use std::{cmp::Ordering, iter};
+
+use crate::radix_trie::{Key, KeyRef, Node, Trie};
+
+/// Iterator over the entries of a [RadixTrie](super::RadixTrie) whose key is
+/// within a Levenshtein (edit) distance of a query key. Created by
+/// [RadixTrie::fuzzy](super::RadixTrie::fuzzy).
+///
+/// Yielded items are a tuple of (P, &V, usize), where the last element is the
+/// distance between the key and the query. Entries are yielded in
+/// lexicographic order of the keys.
+pub struct Fuzzy<'a, P, V>
+where
+    P: Key,
+{
+    // units of the query, e.g. the chars of a `str`
+    query: Vec<&'a P::Ref>,
+    max_distance: usize,
+    // value of the root, with the distance of the empty key to the query
+    root_value: Option<(&'a V, usize)>,
+    // tries being visited, whose rows of distances are within
+    // `max_distance`
+    stack: Vec<FuzzyState<'a, P, V>>,
+    key: P,
+}
+
+struct FuzzyState<'a, P, V>
+where
+    P: Key,
+{
+    // length of the key of the trie, in `Fuzzy::key`
+    key_len: usize,
+    // distances between the key of the trie and each prefix of the query
+    row: Vec<usize>,
+    nodes: &'a [Node<P, V>],
+}
+
+impl<'a, P, V> Fuzzy<'a, P, V>
+where
+    P: Key,
+{
+    pub(super) fn new(tree: &'a Trie<P, V>, query: &'a P::Ref, max_distance: usize) -> Self {
+        let query: Vec<_> = iter::successors(query.split_first(), |(_, rest)| rest.split_first())
+            .map(|(unit, _)| unit)
+            .collect();
+        let row: Vec<_> = (0..=query.len()).collect();
+
+        Fuzzy {
+            root_value: tree
+                .value
+                .as_ref()
+                .map(|value| (value, query.len()))
+                .filter(|&(_, distance)| distance <= max_distance),
+            stack: vec![FuzzyState {
+                key_len: 0,
+                row,
+                nodes: &tree.nodes[..],
+            }],
+            key: P::Ref::concat(&mut iter::empty()),
+            query,
+            max_distance,
+        }
+    }
+
+    /// Extend `row` by one `unit` of the key
+    fn step(&self, row: &mut [usize], unit: &P::Ref) {
+        // distance with the previous unit of the key, to the previous prefix
+        // of the query
+        let mut diagonal = row[0];
+        row[0] += 1;
+        for (idx, query_unit) in self.query.iter().enumerate() {
+            let substitution = match P::Ref::cmp_first(unit, query_unit) {
+                Ordering::Equal => diagonal,
+                _ => diagonal + 1,
+            };
+            diagonal = row[idx + 1];
+            row[idx + 1] = substitution.min(row[idx] + 1).min(row[idx + 1] + 1);
+        }
+    }
+}
+
+impl<'a, P, V> Iterator for Fuzzy<'a, P, V>
+where
+    P: Key,
+{
+    type Item = (P, &'a V, usize);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some((value, distance)) = self.root_value.take() {
+            return Some((self.key.borrow().to_owned(), value, distance));
+        }
+
+        loop {
+            let state = self.stack.last_mut()?;
+            let Some((node, rest)) = state.nodes.split_first() else {
+                self.stack.pop();
+                continue;
+            };
+            state.nodes = rest;
+            let key_len = state.key_len;
+            let mut row = state.row.clone();
+
+            // no key below the node is closer than the smallest distance in
+            // its row, so prune it once that is too far
+            let mut edge = node.key.borrow();
+            let mut pruned = false;
+            while let Some((unit, edge_rest)) = edge.split_first() {
+                self.step(&mut row, unit);
+                if *row.iter().min().unwrap() > self.max_distance {
+                    pruned = true;
+                    break;
+                }
+                edge = edge_rest;
+            }
+            if pruned {
+                continue;
+            }
+
+            self.key.truncate(key_len);
+            self.key.append_ref(node.key.borrow());
+            let distance = row[self.query.len()];
+            self.stack.push(FuzzyState {
+                key_len: self.key.borrow().len(),
+                row,
+                nodes: &node.trie.nodes[..],
+            });
+
+            if let Some(value) = node.trie.value.as_ref() {
+                if distance <= self.max_distance {
+                    return Some((self.key.borrow().to_owned(), value, distance));
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use itertools::assert_equal;
+
+    use crate::radix_trie::{key_bits_impl::BitKey, RadixTrie};
+
+    /// Levenshtein distance between `a` and `b`, one row at a time
+    fn distance(a: &str, b: &str) -> usize {
+        let b: Vec<_> = b.chars().collect();
+        let mut row: Vec<_> = (0..=b.len()).collect();
+        for (idx, a_char) in a.chars().enumerate() {
+            let mut next = vec![idx + 1];
+            for (jdx, b_char) in b.iter().enumerate() {
+                let substitution = row[jdx] + (a_char != *b_char) as usize;
+                next.push(substitution.min(row[jdx + 1] + 1).min(next[jdx] + 1));
+            }
+            row = next;
+        }
+        row[b.len()]
+    }
+
+    fn get_test_trie() -> RadixTrie<String, usize> {
+        [
+            "", "a", "at", "bat", "cat", "cart", "cast", "dog", "dot", "Юa",
+        ]
+        .into_iter()
+        .enumerate()
+        .map(|(idx, key)| (key.to_owned(), idx))
+        .collect()
+    }
+
+    #[test]
+    fn test_works() {
+        let trie = get_test_trie();
+
+        assert_equal(vec![("cat".into(), &4, 0)], trie.fuzzy("cat", 0));
+        assert_equal(
+            vec![
+                ("at".into(), &2, 1),
+                ("bat".into(), &3, 1),
+                ("cart".into(), &5, 1),
+                ("cast".into(), &6, 1),
+                ("cat".into(), &4, 0),
+            ],
+            trie.fuzzy("cat", 1),
+        );
+        assert_equal(
+            vec![
+                ("".into(), &0, 1),
+                ("a".into(), &1, 0),
+                ("at".into(), &2, 1),
+                ("Юa".into(), &9, 1),
+            ],
+            trie.fuzzy("a", 1),
+        );
+        // distances are in units, so "Ю" is one substitution from "b"
+        assert_equal(
+            vec![
+                ("a".into(), &1, 1),
+                ("bat".into(), &3, 1),
+                ("Юa".into(), &9, 1),
+            ],
+            trie.fuzzy("ba", 1),
+        );
+        assert_eq!(0, trie.fuzzy("zzzz", 2).count());
+    }
+
+    #[test]
+    fn test_matches_distance() {
+        let trie = get_test_trie();
+        for query in ["", "a", "cta", "dots", "Ю", "cartz"] {
+            for max_distance in 0..4 {
+                let expected = trie
+                    .iter()
+                    .map(|(key, value)| {
+                        let distance = distance(&key, query);
+                        (key, value, distance)
+                    })
+                    .filter(|&(_, _, distance)| distance <= max_distance);
+                assert_equal(expected, trie.fuzzy(query, max_distance));
+            }
+        }
+    }
+
+    #[test]
+    fn test_bits() {
+        let trie: RadixTrie<BitKey, ()> = [0b1010_1010u8, 0b1010_1011, 0b0101_0101]
+            .into_iter()
+            .map(|byte| (BitKey::from_bytes(&[byte], 8), ()))
+            .collect();
+        let query = BitKey::from_bytes(&[0b1010_1000], 8);
+        assert_eq!(
+            vec![1, 2],
+            trie.fuzzy(&query, 2)
+                .map(|(_, _, distance)| distance)
+                .collect::<Vec<_>>()
+        );
+    }
+}
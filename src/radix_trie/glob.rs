@@ -0,0 +1,359 @@
+use std::path::{Component, Path, PathBuf};
+
+use crate::radix_trie::{
+    automaton::{Automaton, Search},
+    RadixTrie,
+};
+
+/// Glob pattern over the chars of a `str`, as an [Automaton] for
+/// `RadixTrie<String, V>`. `?` matches any char, `*` any number of chars
+/// (including `/`), and `[a-z]` / `[!a-z]` a char within, or not within, a
+/// class. A `\` matches the char after it literally, and a `[` without a
+/// closing `]` matches itself.
+///
+/// A state of the automaton is the set of positions in the pattern that the
+/// chars so far can reach, and a key matches if the end of the pattern is
+/// one of them.
+pub struct Glob {
+    tokens: Vec<Token>,
+}
+
+/// Glob pattern over the components of a `Path`, as an [Automaton] for
+/// `RadixTrie<PathBuf, V>`. Each component of the pattern is a [Glob] over
+/// the chars of one component, so `*` and `?` never match across a `/`,
+/// except for a `**` component, which matches any number of components.
+pub struct PathGlob(Glob);
+
+enum Token {
+    // a single char
+    Char(char),
+    // `?`, any single char
+    Any,
+    // `[...]`, a single char within (or with `!` or `^`, not within) the
+    // inclusive ranges
+    Class {
+        negated: bool,
+        ranges: Vec<(char, char)>,
+    },
+    // `*`, any number of units. Within a path, `**` is any number of
+    // components.
+    Star,
+    // a single path component, matching a glob of chars
+    Component(Glob),
+}
+
+impl Glob {
+    pub fn new(pattern: &str) -> Glob {
+        let chars: Vec<_> = pattern.chars().collect();
+        let mut tokens = vec![];
+        let mut idx = 0;
+
+        while idx < chars.len() {
+            let token = match chars[idx] {
+                '*' => Token::Star,
+                '?' => Token::Any,
+                '\\' if idx + 1 < chars.len() => {
+                    idx += 1;
+                    Token::Char(chars[idx])
+                }
+                '[' => match Self::class(&chars[idx + 1..]) {
+                    Some((token, len)) => {
+                        idx += len;
+                        token
+                    }
+                    None => Token::Char('['),
+                },
+                c => Token::Char(c),
+            };
+            // consecutive stars match the same keys as one
+            if !(matches!(token, Token::Star) && matches!(tokens.last(), Some(Token::Star))) {
+                tokens.push(token);
+            }
+            idx += 1;
+        }
+
+        Glob { tokens }
+    }
+
+    /// Parse a character class from the chars after its `[`, returning it
+    /// and the number of chars up to and including its `]`
+    fn class(chars: &[char]) -> Option<(Token, usize)> {
+        let negated = matches!(chars.first(), Some('!' | '^'));
+        let mut idx = negated as usize;
+        let mut ranges = vec![];
+
+        // a `]` right after the `[` is part of the class
+        while idx < chars.len() && (chars[idx] != ']' || idx == negated as usize) {
+            let start = chars[idx];
+            match chars.get(idx + 1..idx + 3) {
+                Some(&['-', end]) if end != ']' => {
+                    ranges.push((start, end));
+                    idx += 3;
+                }
+                _ => {
+                    ranges.push((start, start));
+                    idx += 1;
+                }
+            }
+        }
+
+        if idx == chars.len() {
+            return None;
+        }
+        Some((Token::Class { negated, ranges }, idx + 1))
+    }
+
+    /// States before any unit
+    fn initial(&self) -> Vec<bool> {
+        let mut states = vec![false; self.tokens.len() + 1];
+        states[0] = true;
+        self.skip_stars(&mut states);
+        states
+    }
+
+    /// States after a unit, where `matches` is whether the unit matches a
+    /// token that isn't a `Star`
+    fn advance(&self, states: &[bool], matches: impl Fn(&Token) -> bool) -> Vec<bool> {
+        let mut next = vec![false; states.len()];
+        for (idx, token) in self.tokens.iter().enumerate() {
+            if !states[idx] {
+                continue;
+            }
+            match token {
+                Token::Star => next[idx] = true,
+                token if matches(token) => next[idx + 1] = true,
+                _ => {}
+            }
+        }
+        self.skip_stars(&mut next);
+        next
+    }
+
+    /// A `Star` can match no units, so a state before one is also after it
+    fn skip_stars(&self, states: &mut [bool]) {
+        for (idx, token) in self.tokens.iter().enumerate() {
+            if states[idx] && matches!(token, Token::Star) {
+                states[idx + 1] = true;
+            }
+        }
+    }
+
+    /// Does all of `text` match the pattern?
+    pub fn matches(&self, text: &str) -> bool {
+        let states = text.chars().fold(self.initial(), |states, c| {
+            self.advance(&states, |token| char_matches(token, c))
+        });
+        states[self.tokens.len()]
+    }
+}
+
+impl PathGlob {
+    pub fn new(pattern: &str) -> PathGlob {
+        let tokens = Path::new(pattern)
+            .components()
+            .map(|component| match component {
+                Component::Normal(name) if name == "**" => Token::Star,
+                component => Token::Component(Glob::new(&component.as_os_str().to_string_lossy())),
+            })
+            .collect();
+        PathGlob(Glob { tokens })
+    }
+}
+
+fn char_matches(token: &Token, c: char) -> bool {
+    match token {
+        Token::Char(expected) => c == *expected,
+        Token::Any => true,
+        Token::Class { negated, ranges } => {
+            ranges.iter().any(|&(start, end)| start <= c && c <= end) != *negated
+        }
+        Token::Star | Token::Component(_) => false,
+    }
+}
+
+impl Automaton<str> for Glob {
+    type State = Vec<bool>;
+
+    fn start(&self) -> Vec<bool> {
+        self.initial()
+    }
+
+    fn step(&self, states: &Vec<bool>, unit: &str) -> Vec<bool> {
+        let c = unit.chars().next().unwrap();
+        self.advance(states, |token| char_matches(token, c))
+    }
+
+    fn is_match(&self, states: &Vec<bool>) -> bool {
+        states[self.tokens.len()]
+    }
+
+    fn can_match(&self, states: &Vec<bool>) -> bool {
+        states.contains(&true)
+    }
+}
+
+impl Automaton<Path> for PathGlob {
+    type State = Vec<bool>;
+
+    fn start(&self) -> Vec<bool> {
+        self.0.initial()
+    }
+
+    fn step(&self, states: &Vec<bool>, unit: &Path) -> Vec<bool> {
+        let name = unit.as_os_str().to_string_lossy();
+        self.0.advance(states, |token| match token {
+            Token::Component(glob) => glob.matches(&name),
+            _ => false,
+        })
+    }
+
+    fn is_match(&self, states: &Vec<bool>) -> bool {
+        self.0.is_match(states)
+    }
+
+    fn can_match(&self, states: &Vec<bool>) -> bool {
+        self.0.can_match(states)
+    }
+}
+
+impl<V> RadixTrie<String, V> {
+    /// Iterate over the entries whose key matches the glob `pattern` (see
+    /// [Glob]), in lexicographic order of the keys. Nodes that no key
+    /// matching the pattern can be below are skipped.
+    pub fn matching(&self, pattern: &str) -> Search<'_, String, V, Glob> {
+        self.search(Glob::new(pattern))
+    }
+}
+
+impl<V> RadixTrie<PathBuf, V> {
+    /// Iterate over the entries whose path matches the glob `pattern` a
+    /// component at a time (see [PathGlob]), in lexicographic order of the
+    /// paths
+    pub fn matching(&self, pattern: &str) -> Search<'_, PathBuf, V, PathGlob> {
+        self.search(PathGlob::new(pattern))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::path::PathBuf;
+
+    use itertools::assert_equal;
+
+    use crate::radix_trie::RadixTrie;
+
+    use super::Glob;
+
+    fn keys<P, V>(iter: impl Iterator<Item = (P, V)>) -> Vec<P> {
+        iter.map(|(key, _)| key).collect()
+    }
+
+    #[test]
+    fn test_glob() {
+        let cases = [
+            ("", "", true),
+            ("", "a", false),
+            ("abc", "abc", true),
+            ("a?c", "abc", true),
+            ("a?c", "ac", false),
+            ("*", "", true),
+            ("a*", "abc/d", true),
+            ("*c", "abc", true),
+            ("a**b*", "ab", true),
+            ("*b*", "ac", false),
+            ("[a-c]x", "bx", true),
+            ("[!a-c]x", "bx", false),
+            ("[^a-c]x", "dx", true),
+            ("[]]", "]", true),
+            ("[a-]", "-", true),
+            ("[ab", "[ab", true),
+            ("\\*", "*", true),
+            ("\\*", "a", false),
+            ("Ю?", "ЮЯ", true),
+        ];
+        for (pattern, text, expected) in cases {
+            assert_eq!(
+                expected,
+                Glob::new(pattern).matches(text),
+                "{pattern:?} {text:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_strings() {
+        let trie: RadixTrie<String, usize> = [
+            "",
+            "log-2024-01-02",
+            "log-2024-11-30",
+            "log-2025-01-01",
+            "user/1/settings",
+            "user/2/profile",
+            "user/2/settings",
+            "user/3/a/settings",
+        ]
+        .into_iter()
+        .enumerate()
+        .map(|(idx, key)| (key.to_owned(), idx))
+        .collect();
+
+        assert_eq!(
+            vec!["log-2024-01-02", "log-2024-11-30"],
+            keys(trie.matching("log-2024-??-*"))
+        );
+        assert_eq!(
+            vec!["log-2024-11-30"],
+            keys(trie.matching("log-202[4-9]-1?-*"))
+        );
+        assert_eq!(
+            vec!["user/1/settings", "user/2/settings", "user/3/a/settings"],
+            keys(trie.matching("user/*/settings"))
+        );
+        assert_eq!(vec![""], keys(trie.matching("")));
+        assert_eq!(trie.iter().count(), trie.matching("*").count());
+        assert_eq!(0, trie.matching("user/?/x*").count());
+        assert_equal(
+            vec![("user/2/profile".into(), &5)],
+            trie.matching("*profile"),
+        );
+    }
+
+    #[test]
+    fn test_paths() {
+        let trie: RadixTrie<PathBuf, ()> = [
+            "/etc/hosts",
+            "/home/a/.config/app/settings.toml",
+            "/home/a/notes.txt",
+            "/home/b/notes.md",
+            "/home/b/old/notes.txt",
+        ]
+        .into_iter()
+        .map(|path| (path.into(), ()))
+        .collect();
+
+        assert_eq!(
+            vec![PathBuf::from("/home/a/notes.txt")],
+            keys(trie.matching("/home/*/notes.txt"))
+        );
+        assert_eq!(
+            vec![
+                PathBuf::from("/home/a/notes.txt"),
+                PathBuf::from("/home/b/notes.md"),
+            ],
+            keys(trie.matching("/home/?/notes.*"))
+        );
+        assert_eq!(
+            vec![
+                PathBuf::from("/home/a/notes.txt"),
+                PathBuf::from("/home/b/old/notes.txt"),
+            ],
+            keys(trie.matching("/home/**/*.txt"))
+        );
+        assert_eq!(
+            vec![PathBuf::from("/home/a/.config/app/settings.toml")],
+            keys(trie.matching("**/settings.[ty]*"))
+        );
+        assert_eq!(5, trie.matching("/**").count());
+        assert_eq!(0, trie.matching("/home/*").count());
+    }
+}
@@ -0,0 +1,243 @@
+use std::{iter, vec};
+
+use crate::radix_trie::{Key, KeyRef, RadixTrie, Trie};
+
+use super::Node;
+
+/// Owning iterator over a [RadixTrie], in lexicographic order of the keys.
+///
+/// Yielded items are a tuple of (P, V). The trie's nodes are taken apart as
+/// the iterator goes, and values are moved out without cloning.
+pub struct IntoIter<P, V>
+where
+    P: Key,
+{
+    stack: Vec<IntoIterState<P, V>>,
+    // key of the node at the top of `stack`, appended to and truncated as
+    // nodes are pushed and popped
+    key: P,
+    // number of values not yet yielded
+    len: usize,
+}
+
+struct IntoIterState<P, V> {
+    key: Option<P>,
+    value: Option<V>,
+    nodes: vec::IntoIter<Node<P, V>>,
+}
+
+impl<P, V> IntoIter<P, V>
+where
+    P: Key,
+{
+    fn to_iter_state(key: Option<P>, tree: Trie<P, V>) -> IntoIterState<P, V> {
+        IntoIterState {
+            key,
+            value: tree.value,
+            nodes: tree.nodes.into_iter(),
+        }
+    }
+}
+
+impl<P, V> Iterator for IntoIter<P, V>
+where
+    P: Key,
+{
+    type Item = (P, V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let state = self.stack.last_mut()?;
+
+            if let Some(value) = state.value.take() {
+                self.len -= 1;
+                return Some((self.key.borrow().to_owned(), value));
+            }
+
+            match state.nodes.next() {
+                Some(Node { key, trie }) => {
+                    self.key.append_ref(key.borrow());
+                    self.stack.push(Self::to_iter_state(Some(key), trie));
+                }
+                None => {
+                    if let Some(key) = self.stack.pop().and_then(|state| state.key) {
+                        let key_len = self.key.borrow().len() - key.borrow().len();
+                        self.key.truncate(key_len);
+                    }
+                }
+            }
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<P, V> ExactSizeIterator for IntoIter<P, V> where P: Key {}
+
+/// Owning iterator over the keys of a [RadixTrie], in lexicographic order
+pub struct IntoKeys<P, V>(pub(super) IntoIter<P, V>)
+where
+    P: Key;
+
+impl<P, V> Iterator for IntoKeys<P, V>
+where
+    P: Key,
+{
+    type Item = P;
+
+    fn next(&mut self) -> Option<P> {
+        self.0.next().map(|(key, _)| key)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+
+impl<P, V> ExactSizeIterator for IntoKeys<P, V> where P: Key {}
+
+/// Owning iterator over the values of a [RadixTrie], in lexicographic order
+/// of their keys. Keys are dropped without being built.
+pub struct IntoValues<P, V> {
+    stack: Vec<IntoIterState<P, V>>,
+    // number of values not yet yielded
+    len: usize,
+}
+
+impl<P, V> IntoValues<P, V>
+where
+    P: Key,
+{
+    pub(super) fn new(trie: RadixTrie<P, V>) -> Self {
+        IntoValues {
+            stack: vec![IntoIter::to_iter_state(None, trie.root)],
+            len: trie.len,
+        }
+    }
+}
+
+impl<P, V> Iterator for IntoValues<P, V>
+where
+    P: Key,
+{
+    type Item = V;
+
+    fn next(&mut self) -> Option<V> {
+        loop {
+            let state = self.stack.last_mut()?;
+
+            if let Some(value) = state.value.take() {
+                self.len -= 1;
+                return Some(value);
+            }
+
+            match state.nodes.next() {
+                Some(Node { trie, .. }) => self.stack.push(IntoIter::to_iter_state(None, trie)),
+                None => {
+                    self.stack.pop();
+                }
+            }
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<P, V> ExactSizeIterator for IntoValues<P, V> where P: Key {}
+
+impl<P, V> IntoIterator for RadixTrie<P, V>
+where
+    P: Key,
+{
+    type Item = (P, V);
+    type IntoIter = IntoIter<P, V>;
+
+    fn into_iter(self) -> IntoIter<P, V> {
+        IntoIter {
+            stack: vec![IntoIter::to_iter_state(None, self.root)],
+            key: P::Ref::concat(&mut iter::empty()),
+            len: self.len,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use itertools::assert_equal;
+
+    use crate::radix_trie::RadixTrie;
+
+    #[test]
+    fn test_works() {
+        let trie = RadixTrie::<String, usize>::new();
+        assert_eq!(0, trie.into_iter().count());
+
+        let mut trie = RadixTrie::<String, _>::new();
+        trie.insert("abc".into(), vec![2]);
+        trie.insert("ab".into(), vec![1]);
+        trie.insert("b".into(), vec![3]);
+        trie.insert("".into(), vec![0]);
+
+        let iter = trie.into_iter();
+        assert_eq!(4, iter.len());
+        assert_equal(
+            vec![
+                ("".into(), vec![0]),
+                ("ab".into(), vec![1]),
+                ("abc".into(), vec![2]),
+                ("b".into(), vec![3]),
+            ],
+            iter,
+        );
+    }
+
+    #[test]
+    fn test_collect_and_extend() {
+        let mut trie: RadixTrie<String, usize> =
+            vec![("b".into(), 2), ("a".into(), 1)].into_iter().collect();
+        trie.extend(vec![("ab".into(), 3), ("a".into(), 4)]);
+        assert_eq!(3, trie.len());
+
+        for (_, value) in &mut trie {
+            *value *= 10;
+        }
+
+        let mut keys = vec![];
+        for (key, value) in &trie {
+            keys.push(key);
+            assert_eq!(trie.get(&keys[keys.len() - 1]), Some(value));
+        }
+        assert_eq!(vec!["a", "ab", "b"], keys);
+
+        assert_equal(
+            vec![("a".into(), 40), ("ab".into(), 30), ("b".into(), 20)],
+            trie,
+        );
+    }
+
+    #[test]
+    fn test_into_keys_and_values() {
+        let get_trie = || -> RadixTrie<String, _> {
+            vec![
+                ("abc".into(), vec![2]),
+                ("ab".into(), vec![1]),
+                ("b".into(), vec![3]),
+            ]
+            .into_iter()
+            .collect()
+        };
+
+        let keys = get_trie().into_keys();
+        assert_eq!(3, keys.len());
+        assert_eq!(vec!["ab", "abc", "b"], keys.collect::<Vec<_>>());
+
+        let mut values = get_trie().into_values();
+        assert_eq!(Some(vec![1]), values.next());
+        assert_eq!(2, values.len());
+        assert_equal(vec![vec![2], vec![3]], values);
+    }
+}
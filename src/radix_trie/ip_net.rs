@@ -0,0 +1,188 @@
+use std::net::{Ipv4Addr, Ipv6Addr};
+
+use super::{
+    key::KeyRef,
+    key_bits_impl::{BitKey, Bits},
+};
+
+/// IPv4 network in CIDR notation (e.g. `10.0.16.0/20`), which converts into a
+/// [BitKey] of its first `prefix_len` address bits.
+///
+/// Networks stored in a `RadixTrie<BitKey, V>` can be matched against
+/// addresses with [RadixTrie::longest_prefix](super::RadixTrie::longest_prefix).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Ipv4Net {
+    addr: Ipv4Addr,
+    prefix_len: u8,
+}
+
+/// IPv6 network in CIDR notation (e.g. `2001:db8::/32`), which converts into a
+/// [BitKey] of its first `prefix_len` address bits.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Ipv6Net {
+    addr: Ipv6Addr,
+    prefix_len: u8,
+}
+
+impl Ipv4Net {
+    /// Create the network of `addr` with a prefix length of `prefix_len`
+    /// bits. Address bits past the prefix are cleared. `None` if
+    /// `prefix_len` is greater than 32.
+    pub fn new(addr: Ipv4Addr, prefix_len: u8) -> Option<Ipv4Net> {
+        if prefix_len > 32 {
+            return None;
+        }
+        let mask = u32::MAX.checked_shl(32 - prefix_len as u32).unwrap_or(0);
+        Some(Ipv4Net {
+            addr: Ipv4Addr::from(u32::from(addr) & mask),
+            prefix_len,
+        })
+    }
+
+    /// Convert a key (e.g. one returned by
+    /// [RadixTrie::longest_prefix](super::RadixTrie::longest_prefix)) back
+    /// into a network. `None` if the key is longer than 32 bits.
+    pub fn from_bits(bits: &Bits) -> Option<Ipv4Net> {
+        let mut octets = [0; 4];
+        let bytes = bits.to_bytes();
+        octets.get_mut(..bytes.len())?.copy_from_slice(&bytes);
+        Ipv4Net::new(Ipv4Addr::from(octets), bits.len() as u8)
+    }
+
+    /// Network address
+    pub fn addr(&self) -> Ipv4Addr {
+        self.addr
+    }
+
+    /// Number of leading address bits that make up the network
+    pub fn prefix_len(&self) -> u8 {
+        self.prefix_len
+    }
+}
+
+impl Ipv6Net {
+    /// Create the network of `addr` with a prefix length of `prefix_len`
+    /// bits. Address bits past the prefix are cleared. `None` if
+    /// `prefix_len` is greater than 128.
+    pub fn new(addr: Ipv6Addr, prefix_len: u8) -> Option<Ipv6Net> {
+        if prefix_len > 128 {
+            return None;
+        }
+        let mask = u128::MAX.checked_shl(128 - prefix_len as u32).unwrap_or(0);
+        Some(Ipv6Net {
+            addr: Ipv6Addr::from(u128::from(addr) & mask),
+            prefix_len,
+        })
+    }
+
+    /// Convert a key back into a network. `None` if the key is longer than
+    /// 128 bits.
+    pub fn from_bits(bits: &Bits) -> Option<Ipv6Net> {
+        let mut octets = [0; 16];
+        let bytes = bits.to_bytes();
+        octets.get_mut(..bytes.len())?.copy_from_slice(&bytes);
+        Ipv6Net::new(Ipv6Addr::from(octets), bits.len() as u8)
+    }
+
+    /// Network address
+    pub fn addr(&self) -> Ipv6Addr {
+        self.addr
+    }
+
+    /// Number of leading address bits that make up the network
+    pub fn prefix_len(&self) -> u8 {
+        self.prefix_len
+    }
+}
+
+impl From<Ipv4Net> for BitKey {
+    fn from(net: Ipv4Net) -> BitKey {
+        BitKey::from_bytes(&net.addr.octets(), net.prefix_len as usize)
+    }
+}
+
+impl From<Ipv6Net> for BitKey {
+    fn from(net: Ipv6Net) -> BitKey {
+        BitKey::from_bytes(&net.addr.octets(), net.prefix_len as usize)
+    }
+}
+
+/// All 32 bits of the address, for looking up the networks containing it
+impl From<Ipv4Addr> for BitKey {
+    fn from(addr: Ipv4Addr) -> BitKey {
+        BitKey::from_bytes(&addr.octets(), 32)
+    }
+}
+
+/// All 128 bits of the address, for looking up the networks containing it
+impl From<Ipv6Addr> for BitKey {
+    fn from(addr: Ipv6Addr) -> BitKey {
+        BitKey::from_bytes(&addr.octets(), 128)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::net::{Ipv4Addr, Ipv6Addr};
+
+    use super::{Ipv4Net, Ipv6Net};
+    use crate::radix_trie::{key_bits_impl::BitKey, RadixTrie};
+
+    fn v4(a: u8, b: u8, c: u8, d: u8, prefix_len: u8) -> BitKey {
+        Ipv4Net::new(Ipv4Addr::new(a, b, c, d), prefix_len)
+            .unwrap()
+            .into()
+    }
+
+    #[test]
+    fn test_new() {
+        let net = Ipv4Net::new(Ipv4Addr::new(10, 0, 31, 1), 20).unwrap();
+        assert_eq!(Ipv4Addr::new(10, 0, 16, 0), net.addr());
+        assert_eq!(20, net.prefix_len());
+        assert_eq!(
+            Ipv4Addr::new(0, 0, 0, 0),
+            Ipv4Net::new(Ipv4Addr::new(10, 0, 0, 1), 0).unwrap().addr()
+        );
+        assert_eq!(None, Ipv4Net::new(Ipv4Addr::new(10, 0, 0, 1), 33));
+
+        let net = Ipv6Net::new("2001:db8:ffff::1".parse().unwrap(), 32).unwrap();
+        assert_eq!("2001:db8::".parse::<Ipv6Addr>().unwrap(), net.addr());
+        assert_eq!(None, Ipv6Net::new(Ipv6Addr::LOCALHOST, 129));
+    }
+
+    #[test]
+    fn test_from_bits() {
+        let net = Ipv4Net::new(Ipv4Addr::new(192, 168, 16, 0), 20).unwrap();
+        assert_eq!(Some(net), Ipv4Net::from_bits(&BitKey::from(net)));
+        assert_eq!(None, Ipv4Net::from_bits(&BitKey::from(Ipv6Addr::LOCALHOST)));
+
+        let net = Ipv6Net::new("2001:db8::".parse().unwrap(), 33).unwrap();
+        assert_eq!(Some(net), Ipv6Net::from_bits(&BitKey::from(net)));
+    }
+
+    #[test]
+    fn test_longest_prefix_match() {
+        let mut trie: RadixTrie<BitKey, &str> = RadixTrie::new();
+        trie.insert(v4(0, 0, 0, 0, 0), "default");
+        trie.insert(v4(10, 0, 0, 0, 8), "10/8");
+        trie.insert(v4(10, 0, 16, 0, 20), "10.0.16/20");
+        trie.insert(v4(10, 0, 17, 0, 24), "10.0.17/24");
+
+        let lookup = |addr: Ipv4Addr| {
+            let addr = BitKey::from(addr);
+            let (bits, value) = trie.longest_prefix(&addr).unwrap();
+            (Ipv4Net::from_bits(bits).unwrap(), *value)
+        };
+
+        assert_eq!(
+            (
+                Ipv4Net::new(Ipv4Addr::new(10, 0, 16, 0), 20).unwrap(),
+                "10.0.16/20"
+            ),
+            lookup(Ipv4Addr::new(10, 0, 31, 255))
+        );
+        assert_eq!("10.0.17/24", lookup(Ipv4Addr::new(10, 0, 17, 3)).1);
+        assert_eq!("10/8", lookup(Ipv4Addr::new(10, 0, 32, 0)).1);
+        assert_eq!("default", lookup(Ipv4Addr::new(11, 0, 0, 0)).1);
+    }
+}
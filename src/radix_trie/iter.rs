@@ -1,37 +1,272 @@
-use crate::radix_trie::{Key, KeyRef, RadixTrie};
+use std::{cmp::Ordering, collections::VecDeque, iter, ops::Bound};
+
+use crate::radix_trie::{Key, KeyRef, RadixTrie, Trie};
 
 use super::Node;
 
-/// Iterator over a [RadixTrie]
+/// Iterator over a [RadixTrie](super::RadixTrie)
 ///
 /// Yielded items are a tuple of (P, &V), where
 /// P is the key type, and V is the value type
-pub struct Iter<'a, P, V>(Vec<IterState<'a, P, V>>)
+pub struct Iter<'a, P, V>
 where
-    P: Key;
+    P: Key,
+{
+    // nodes not yet visited, in order. The front of the iterator works on
+    // the first state, and the back on the last. The first `front_depth`
+    // states are descendants pushed by the front, each the child of the
+    // next; the states after the one at `front_depth` are descendants
+    // pushed by the back, each the child of the previous.
+    states: VecDeque<IterState<'a, P, V>>,
+    front_depth: usize,
+    // keys of the first and last states, appended to and truncated as
+    // states are pushed and popped
+    front_key: P,
+    back_key: P,
+}
 
-pub struct IterState<'a, P, V>
+struct IterState<'a, P, V>
 where
     P: Key,
 {
     key: Option<&'a P::Ref>,
     value: Option<&'a V>,
-    nodes: Option<&'a [Node<P, V>]>,
+    nodes: &'a [Node<P, V>],
 }
 
 impl<'a, P, V> Iter<'a, P, V>
 where
     P: Key,
 {
-    pub(super) fn new(tree: &'a RadixTrie<P, V>) -> Self {
-        Iter(vec![Self::to_iter_state(None, tree)])
+    pub(super) fn new(tree: &'a Trie<P, V>) -> Self {
+        Self::with_path(vec![], tree)
     }
 
-    fn to_iter_state(key: Option<&'a P::Ref>, tree: &'a RadixTrie<P, V>) -> IterState<'a, P, V> {
-        IterState {
-            key,
-            value: tree.value.as_ref(),
-            nodes: Some(&tree.nodes[..]),
+    /// Iterator over `tree`, where the keys of the nodes leading to `tree`
+    /// are `path`
+    pub(super) fn with_path(path: Vec<&'a P::Ref>, tree: &'a Trie<P, V>) -> Self {
+        Iter {
+            states: VecDeque::from([IterState::new(None, tree)]),
+            front_depth: 0,
+            front_key: P::Ref::concat(&mut path.iter().copied()),
+            back_key: P::Ref::concat(&mut path.into_iter()),
+        }
+    }
+
+    /// Iterator that yields nothing
+    pub(super) fn empty() -> Self {
+        Iter {
+            states: VecDeque::new(),
+            front_depth: 0,
+            front_key: P::Ref::concat(&mut iter::empty()),
+            back_key: P::Ref::concat(&mut iter::empty()),
+        }
+    }
+
+    /// Iterator over the keys in `tree` that are within `start` and `end`.
+    /// Descends to where `start` and `end` are, or would be, skipping over
+    /// the nodes outside of them.
+    pub(super) fn with_range(
+        tree: &'a Trie<P, V>,
+        start: Bound<&P::Ref>,
+        end: Bound<&P::Ref>,
+    ) -> Self {
+        let mut iter = Self::with_start(tree, start);
+        iter.seek_end(end);
+        iter
+    }
+
+    /// Iterator over the keys in `tree` that are after `start`
+    fn with_start(tree: &'a Trie<P, V>, start: Bound<&P::Ref>) -> Self {
+        let (mut query, included) = match start {
+            Bound::Unbounded => return Self::new(tree),
+            Bound::Included(query) => (query, true),
+            Bound::Excluded(query) => (query, false),
+        };
+        let mut iter = Self::empty();
+        let (mut trie, mut key) = (tree, None);
+
+        loop {
+            // the key of `trie` is a proper prefix of `start`, so comes
+            // before it, unless `query` is empty
+            if query.is_empty() {
+                iter.states.push_front(IterState {
+                    key,
+                    value: trie.value.as_ref().filter(|_| included),
+                    nodes: &trie.nodes[..],
+                });
+                break;
+            }
+
+            let idx = match trie.child_idx(query) {
+                Err(idx) => idx,
+                Ok(idx) => {
+                    let node = &trie.nodes[idx];
+                    let (_, node_rest, query_rest) = P::Ref::prefix(node.key.borrow(), query);
+                    if node_rest.is_empty() {
+                        // `start` is within the child, continue with it
+                        iter.states.push_front(IterState {
+                            key,
+                            value: None,
+                            nodes: &trie.nodes[idx + 1..],
+                        });
+                        iter.front_key.append_ref(node.key.borrow());
+                        (trie, key, query) = (&node.trie, Some(node.key.borrow()), query_rest);
+                        continue;
+                    }
+
+                    // the keys in the child are either all before or all
+                    // after `start`
+                    match P::Ref::cmp_first(node_rest, query_rest) {
+                        Ordering::Less => idx + 1,
+                        _ => idx,
+                    }
+                }
+            };
+
+            iter.states.push_front(IterState {
+                key,
+                value: None,
+                nodes: &trie.nodes[idx..],
+            });
+            break;
+        }
+
+        iter.front_depth = iter.states.len() - 1;
+        iter
+    }
+
+    /// Skip over the keys after `end` at the back of the iterator. The
+    /// back must not have moved yet.
+    fn seek_end(&mut self, end: Bound<&P::Ref>) {
+        let (mut query, included) = match end {
+            Bound::Unbounded => return,
+            Bound::Included(query) => (query, true),
+            Bound::Excluded(query) => (query, false),
+        };
+
+        loop {
+            // the front's descendants are all within the shared state
+            let shared = self.states.len() == self.front_depth + 1;
+            let Some(state) = self.states.back_mut() else {
+                return;
+            };
+
+            if query.is_empty() {
+                state.nodes = &[];
+                if !included {
+                    state.value = None;
+                }
+                if shared && self.front_depth > 0 {
+                    self.states.clear();
+                }
+                return;
+            }
+
+            let nodes = state.nodes;
+            let idx =
+                match nodes.binary_search_by(|node| P::Ref::cmp_first(node.key.borrow(), query)) {
+                    Err(idx) => idx,
+                    Ok(idx) => {
+                        let node = &nodes[idx];
+                        let (_, node_rest, query_rest) = P::Ref::prefix(node.key.borrow(), query);
+                        if node_rest.is_empty() {
+                            // `end` is within the child, continue with it
+                            state.nodes = &nodes[..idx];
+                            self.back_key.append_ref(node.key.borrow());
+                            self.states
+                                .push_back(IterState::new(Some(node.key.borrow()), &node.trie));
+                            query = query_rest;
+                            continue;
+                        }
+
+                        match P::Ref::cmp_first(node_rest, query_rest) {
+                            Ordering::Less => idx + 1,
+                            _ => idx,
+                        }
+                    }
+                };
+            state.nodes = &nodes[..idx];
+            if idx > 0 || !shared || self.front_depth == 0 {
+                return;
+            }
+
+            // `end` is before every node after the child the front is in,
+            // so may be within that child
+            self.states.pop_back();
+            self.front_depth -= 1;
+            let child_key = self.states.back().and_then(|state| state.key).unwrap();
+            self.back_key.append_ref(child_key);
+            let (_, child_rest, query_rest) = P::Ref::prefix(child_key, query);
+            if !child_rest.is_empty() {
+                if P::Ref::cmp_first(child_rest, query_rest) != Ordering::Less {
+                    self.states.clear();
+                }
+                return;
+            }
+            query = query_rest;
+        }
+    }
+
+    /// Call `f` with each remaining key and value. Keys are borrowed from a
+    /// buffer owned by the iterator, so unlike [Iterator::for_each], no key
+    /// is allocated.
+    pub fn for_each_with_key<F>(mut self, mut f: F)
+    where
+        F: FnMut(&P::Ref, &'a V),
+    {
+        while let Some((key, value)) = self.next_with_key() {
+            f(key, value);
+        }
+    }
+
+    /// Next key and value, with the key borrowed from `self.front_key`
+    pub(crate) fn next_with_key(&mut self) -> Option<(&P::Ref, &'a V)> {
+        loop {
+            match self.states.front_mut()?.next() {
+                Some(IterStateItem::Value(value)) => return Some((self.front_key.borrow(), value)),
+                Some(IterStateItem::Trie(key, trie)) => {
+                    self.front_key.append_ref(key);
+                    self.states.push_front(IterState::new(Some(key), trie));
+                    self.front_depth += 1;
+                }
+                None => {
+                    let state = self.states.pop_front()?;
+                    if self.front_depth > 0 {
+                        self.front_depth -= 1;
+                        let key_len = self.front_key.borrow().len() - state.key.unwrap().len();
+                        self.front_key.truncate(key_len);
+                    } else if let Some(next) = self.states.front() {
+                        // continue with the back's descendants
+                        self.front_key.append_ref(next.key.unwrap());
+                    }
+                }
+            }
+        }
+    }
+
+    /// Next key and value from the back, with the key borrowed from
+    /// `self.back_key`
+    pub(super) fn next_back_with_key(&mut self) -> Option<(&P::Ref, &'a V)> {
+        loop {
+            match self.states.back_mut()?.next_back() {
+                Some(IterStateItem::Value(value)) => return Some((self.back_key.borrow(), value)),
+                Some(IterStateItem::Trie(key, trie)) => {
+                    self.back_key.append_ref(key);
+                    self.states.push_back(IterState::new(Some(key), trie));
+                }
+                None => {
+                    let state = self.states.pop_back()?;
+                    if self.states.len() > self.front_depth {
+                        let key_len = self.back_key.borrow().len() - state.key.unwrap().len();
+                        self.back_key.truncate(key_len);
+                    } else if let Some(next) = self.states.back() {
+                        // continue with the front's descendants
+                        self.front_depth -= 1;
+                        self.back_key.append_ref(next.key.unwrap());
+                    }
+                }
+            }
         }
     }
 }
@@ -43,16 +278,73 @@ where
     type Item = (P, &'a V);
 
     fn next(&mut self) -> Option<Self::Item> {
+        self.next_with_key()
+            .map(|(key, value)| (key.to_owned(), value))
+    }
+}
+
+impl<'a, P, V> DoubleEndedIterator for Iter<'a, P, V>
+where
+    P: Key,
+{
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.next_back_with_key()
+            .map(|(key, value)| (key.to_owned(), value))
+    }
+}
+
+/// Iterator over the keys of a [RadixTrie](super::RadixTrie), in
+/// lexicographic order
+pub struct Keys<'a, P, V>(pub(super) Iter<'a, P, V>)
+where
+    P: Key;
+
+impl<'a, P, V> Iterator for Keys<'a, P, V>
+where
+    P: Key,
+{
+    type Item = P;
+
+    fn next(&mut self) -> Option<P> {
+        self.0.next().map(|(key, _)| key)
+    }
+}
+
+impl<'a, P, V> DoubleEndedIterator for Keys<'a, P, V>
+where
+    P: Key,
+{
+    fn next_back(&mut self) -> Option<P> {
+        self.0.next_back().map(|(key, _)| key)
+    }
+}
+
+/// Iterator over the values of a [RadixTrie](super::RadixTrie), in
+/// lexicographic order of their keys. Keys are never built.
+pub struct Values<'a, P, V>(Vec<IterState<'a, P, V>>)
+where
+    P: Key;
+
+impl<'a, P, V> Values<'a, P, V>
+where
+    P: Key,
+{
+    pub(super) fn new(tree: &'a Trie<P, V>) -> Self {
+        Values(vec![IterState::new(None, tree)])
+    }
+}
+
+impl<'a, P, V> Iterator for Values<'a, P, V>
+where
+    P: Key,
+{
+    type Item = &'a V;
+
+    fn next(&mut self) -> Option<&'a V> {
         loop {
             match self.0.last_mut().and_then(|node_it| node_it.next()) {
-                Some(IterStateItem::Value(value)) => {
-                    let mut key_iter = self.0.iter().filter_map(|e| e.key);
-                    let key = P::Ref::concat(&mut key_iter);
-                    return Some((key, value));
-                }
-                Some(IterStateItem::Trie(key, trie)) => {
-                    self.0.push(Self::to_iter_state(Some(key), trie))
-                }
+                Some(IterStateItem::Value(value)) => return Some(value),
+                Some(IterStateItem::Trie(_, trie)) => self.0.push(IterState::new(None, trie)),
                 None => {
                     self.0.pop()?;
                 }
@@ -61,12 +353,34 @@ where
     }
 }
 
-pub enum IterStateItem<'a, P, V>
+enum IterStateItem<'a, P, V>
 where
     P: Key,
 {
     Value(&'a V),
-    Trie(&'a P::Ref, &'a RadixTrie<P, V>),
+    Trie(&'a P::Ref, &'a Trie<P, V>),
+}
+
+impl<'a, P, V> IterState<'a, P, V>
+where
+    P: Key,
+{
+    fn new(key: Option<&'a P::Ref>, tree: &'a Trie<P, V>) -> Self {
+        IterState {
+            key,
+            value: tree.value.as_ref(),
+            nodes: &tree.nodes[..],
+        }
+    }
+
+    /// Next item from the back: the nodes from the last, then the value
+    fn next_back(&mut self) -> Option<IterStateItem<'a, P, V>> {
+        if let Some((last, rest)) = self.nodes.split_last() {
+            self.nodes = rest;
+            return Some(IterStateItem::Trie(last.key.borrow(), &last.trie));
+        }
+        self.value.take().map(IterStateItem::Value)
+    }
 }
 
 impl<'a, P, V> Iterator for IterState<'a, P, V>
@@ -79,10 +393,21 @@ where
         if let Some(value) = self.value.take() {
             return Some(IterStateItem::Value(value));
         }
-        let nodes = self.nodes.take()?;
-        let (head, rest) = nodes.split_first()?;
-        self.nodes = Some(rest);
-        return Some(IterStateItem::Trie(head.key.borrow(), &head.trie));
+        let (head, rest) = self.nodes.split_first()?;
+        self.nodes = rest;
+        Some(IterStateItem::Trie(head.key.borrow(), &head.trie))
+    }
+}
+
+impl<'a, P, V> IntoIterator for &'a RadixTrie<P, V>
+where
+    P: Key,
+{
+    type Item = (P, &'a V);
+    type IntoIter = Iter<'a, P, V>;
+
+    fn into_iter(self) -> Iter<'a, P, V> {
+        self.iter()
     }
 }
 
@@ -96,17 +421,14 @@ mod test {
     fn test_works() {
         let mut trie = RadixTrie::<String, _>::new();
         trie.insert("".into(), 1);
-        assert_equal(vec![("".into(), &1)].into_iter(), trie.iter());
+        assert_equal(vec![("".into(), &1)], trie.iter());
 
         trie.insert("a".into(), 2);
-        assert_equal(
-            vec![("".into(), &1), ("a".into(), &2)].into_iter(),
-            trie.iter(),
-        );
+        assert_equal(vec![("".into(), &1), ("a".into(), &2)], trie.iter());
 
         trie.insert("b".into(), 3);
         assert_equal(
-            vec![("".into(), &1), ("a".into(), &2), ("b".into(), &3)].into_iter(),
+            vec![("".into(), &1), ("a".into(), &2), ("b".into(), &3)],
             trie.iter(),
         );
 
@@ -117,8 +439,7 @@ mod test {
                 ("a".into(), &2),
                 ("abc".into(), &4),
                 ("b".into(), &3),
-            ]
-            .into_iter(),
+            ],
             trie.iter(),
         );
 
@@ -130,9 +451,171 @@ mod test {
                 ("ab".into(), &5),
                 ("abc".into(), &4),
                 ("b".into(), &3),
-            ]
-            .into_iter(),
+            ],
+            trie.iter(),
+        );
+    }
+
+    #[test]
+    fn test_lexicographic_order() {
+        let mut trie = RadixTrie::<String, _>::new();
+        for (idx, key) in ["dots", "b", "dog", "Я", "a", "do", "Ю", "abc"]
+            .into_iter()
+            .enumerate()
+        {
+            trie.insert(key.into(), idx);
+        }
+        assert_equal(
+            vec![
+                ("a".into(), &4),
+                ("abc".into(), &7),
+                ("b".into(), &1),
+                ("do".into(), &5),
+                ("dog".into(), &2),
+                ("dots".into(), &0),
+                ("Ю".into(), &6),
+                ("Я".into(), &3),
+            ],
+            trie.iter(),
+        );
+
+        // order is kept after nodes are removed and merged
+        trie.remove("a");
+        trie.remove("do");
+        trie.remove("b");
+        trie.insert("aa".into(), 8);
+        assert_equal(
+            vec![
+                ("aa".into(), &8),
+                ("abc".into(), &7),
+                ("dog".into(), &2),
+                ("dots".into(), &0),
+                ("Ю".into(), &6),
+                ("Я".into(), &3),
+            ],
             trie.iter(),
         );
     }
+
+    #[test]
+    fn test_iter_prefix() {
+        let mut trie = RadixTrie::<String, _>::new();
+        trie.insert("".into(), 0);
+        trie.insert("do".into(), 1);
+        trie.insert("dog".into(), 2);
+        trie.insert("dots".into(), 3);
+        trie.insert("dotty".into(), 4);
+        trie.insert("cat".into(), 5);
+
+        assert_equal(trie.iter(), trie.iter_prefix(""));
+        assert_equal(
+            vec![
+                ("do".into(), &1),
+                ("dog".into(), &2),
+                ("dots".into(), &3),
+                ("dotty".into(), &4),
+            ],
+            trie.iter_prefix("d"),
+        );
+        assert_equal(
+            vec![("dots".into(), &3), ("dotty".into(), &4)],
+            trie.iter_prefix("dot"),
+        );
+        // prefix ends partway through a node's key
+        assert_equal(vec![("dotty".into(), &4)], trie.iter_prefix("dott"));
+        assert_equal(vec![("cat".into(), &5)], trie.iter_prefix("ca"));
+        assert_equal(vec![("cat".into(), &5)], trie.iter_prefix("cat"));
+
+        assert_eq!(0, trie.iter_prefix("cats").count());
+        assert_eq!(0, trie.iter_prefix("dox").count());
+        assert_eq!(0, trie.iter_prefix("e").count());
+    }
+
+    #[test]
+    fn test_for_each_with_key() {
+        let mut trie = RadixTrie::<String, _>::new();
+        for (idx, key) in ["", "do", "dog", "dots", "dotty", "cat"]
+            .into_iter()
+            .enumerate()
+        {
+            trie.insert(key.into(), idx);
+        }
+
+        let mut items = vec![];
+        trie.for_each_with_key(|key, value| items.push((key.to_owned(), value)));
+        assert_equal(trie.iter(), items);
+
+        let mut items = vec![];
+        trie.iter_prefix("dot")
+            .for_each_with_key(|key, value| items.push((key.to_owned(), value)));
+        assert_equal(vec![("dots".into(), &3), ("dotty".into(), &4)], items);
+
+        // picks up where the iterator left off
+        let mut iter = trie.iter();
+        assert_eq!(Some(("".into(), &0)), iter.next());
+        assert_eq!(Some(("cat".into(), &5)), iter.next());
+        let mut keys = vec![];
+        iter.for_each_with_key(|key, _| keys.push(key.to_owned()));
+        assert_eq!(vec!["do", "dog", "dots", "dotty"], keys);
+    }
+
+    #[test]
+    fn test_keys_and_values() {
+        let mut trie = RadixTrie::<String, _>::new();
+        assert_eq!(0, trie.keys().count());
+        assert_eq!(0, trie.values().count());
+
+        for (idx, key) in ["dots", "", "dog", "do", "cat"].into_iter().enumerate() {
+            trie.insert(key.into(), idx);
+        }
+        assert_eq!(
+            vec!["", "cat", "do", "dog", "dots"],
+            trie.keys().collect::<Vec<_>>()
+        );
+        assert_equal(vec![&1, &4, &3, &2, &0], trie.values());
+        assert_eq!(10, trie.values().sum::<usize>());
+    }
+
+    #[test]
+    fn test_double_ended() {
+        let mut trie = RadixTrie::<String, _>::new();
+        for (idx, key) in ["", "do", "dog", "dots", "dotty", "cat", "Ю"]
+            .into_iter()
+            .enumerate()
+        {
+            trie.insert(key.into(), idx);
+        }
+
+        let mut items: Vec<_> = trie.iter().collect();
+        items.reverse();
+        assert_equal(items, trie.iter().rev());
+        assert_equal(
+            vec!["dotty", "dots", "dog", "do"],
+            trie.keys().rev().filter(|key| key.starts_with('d')),
+        );
+        assert_equal(
+            vec![("dotty".into(), &4), ("dots".into(), &3)],
+            trie.iter_prefix("dot").rev(),
+        );
+
+        // the ends meet in the middle, without yielding an entry twice
+        let mut iter = trie.iter();
+        assert_eq!(Some(("".into(), &0)), iter.next());
+        assert_eq!(Some(("Ю".into(), &6)), iter.next_back());
+        assert_eq!(Some(("dotty".into(), &4)), iter.next_back());
+        assert_eq!(Some(("cat".into(), &5)), iter.next());
+        assert_eq!(Some(("do".into(), &1)), iter.next());
+        assert_eq!(Some(("dots".into(), &3)), iter.next_back());
+        assert_eq!(Some(("dog".into(), &2)), iter.next());
+        assert_eq!(None, iter.next_back());
+        assert_eq!(None, iter.next());
+
+        let mut iter = trie.iter_prefix("do");
+        assert_eq!(Some(("dotty".into(), &4)), iter.next_back());
+        assert_eq!(Some(("do".into(), &1)), iter.next());
+        assert_eq!(Some(("dots".into(), &3)), iter.next_back());
+        assert_eq!(Some(("dog".into(), &2)), iter.next_back());
+        assert_eq!(None, iter.next());
+        assert_eq!(None, iter.next_back());
+    }
 }
@@ -1,40 +1,266 @@
-use crate::radix_trie::{Key, KeyRef, RadixTrie};
+use std::{cmp::Ordering, collections::VecDeque, iter, mem, ops::Bound};
+
+use crate::radix_trie::{Key, KeyRef, RadixTrie, Trie};
 
 use super::Node;
 
-/// Mutable iterator over a [RadixTrie]
+/// Mutable iterator over a [RadixTrie](super::RadixTrie)
 ///
 /// Yielded items are a tuple of (P, &mut V), where
 /// P is the key type, and V is the value type
-pub struct IterMut<'a, P, V>(Vec<IterStateMut<'a, P, V>>)
+pub struct IterMut<'a, P, V>
 where
-    P: Key;
+    P: Key,
+{
+    // laid out as for `Iter`. Each node is taken off of the state it's in by
+    // one end, so the ends never borrow the same node.
+    states: VecDeque<IterStateMut<'a, P, V>>,
+    front_depth: usize,
+    front_key: P,
+    back_key: P,
+}
 
-pub struct IterStateMut<'a, P, V>
+struct IterStateMut<'a, P, V>
 where
     P: Key,
 {
     key: Option<&'a P::Ref>,
     value: Option<&'a mut V>,
-    nodes: Option<&'a mut [Node<P, V>]>,
+    nodes: &'a mut [Node<P, V>],
 }
 
 impl<'a, P, V> IterMut<'a, P, V>
 where
     P: Key,
 {
-    pub(super) fn new(tree: &'a mut RadixTrie<P, V>) -> Self {
-        IterMut(vec![Self::to_iter_state(None, tree)])
+    pub(super) fn new(tree: &'a mut Trie<P, V>) -> Self {
+        Self::with_path(vec![], tree)
     }
 
-    fn to_iter_state(
-        key: Option<&'a P::Ref>,
-        tree: &'a mut RadixTrie<P, V>,
-    ) -> IterStateMut<'a, P, V> {
-        IterStateMut {
-            key,
-            value: tree.value.as_mut(),
-            nodes: Some(&mut tree.nodes[..]),
+    /// Iterator over `tree`, where the keys of the nodes leading to `tree`
+    /// are `path`
+    pub(super) fn with_path(path: Vec<&'a P::Ref>, tree: &'a mut Trie<P, V>) -> Self {
+        IterMut {
+            states: VecDeque::from([IterStateMut::new(None, tree)]),
+            front_depth: 0,
+            front_key: P::Ref::concat(&mut path.iter().copied()),
+            back_key: P::Ref::concat(&mut path.into_iter()),
+        }
+    }
+
+    /// Iterator that yields nothing
+    pub(super) fn empty() -> Self {
+        IterMut {
+            states: VecDeque::new(),
+            front_depth: 0,
+            front_key: P::Ref::concat(&mut iter::empty()),
+            back_key: P::Ref::concat(&mut iter::empty()),
+        }
+    }
+
+    /// Mutable version of `Iter::with_range`
+    pub(super) fn with_range(
+        tree: &'a mut Trie<P, V>,
+        start: Bound<&P::Ref>,
+        end: Bound<&P::Ref>,
+    ) -> Self {
+        let mut iter = Self::with_start(tree, start);
+        iter.seek_end(end);
+        iter
+    }
+
+    /// Mutable version of `Iter::with_start`
+    fn with_start(tree: &'a mut Trie<P, V>, start: Bound<&P::Ref>) -> Self {
+        let (mut query, included) = match start {
+            Bound::Unbounded => return Self::new(tree),
+            Bound::Included(query) => (query, true),
+            Bound::Excluded(query) => (query, false),
+        };
+        let mut iter = Self::empty();
+        let (mut trie, mut key) = (tree, None);
+
+        loop {
+            if query.is_empty() {
+                iter.states.push_front(IterStateMut {
+                    key,
+                    value: trie.value.as_mut().filter(|_| included),
+                    nodes: &mut trie.nodes[..],
+                });
+                break;
+            }
+
+            let idx = match trie.child_idx(query) {
+                Err(idx) => idx,
+                Ok(idx) => {
+                    let (_, node_rest, query_rest) =
+                        P::Ref::prefix(trie.nodes[idx].key.borrow(), query);
+                    let order = P::Ref::cmp_first(node_rest, query_rest);
+                    if node_rest.is_empty() {
+                        let (nodes, rest) = trie.nodes.split_at_mut(idx + 1);
+                        let node = &mut nodes[idx];
+                        let node_key: &'a P::Ref = node.key.borrow();
+                        iter.states.push_front(IterStateMut {
+                            key,
+                            value: None,
+                            nodes: rest,
+                        });
+                        iter.front_key.append_ref(node_key);
+                        let (_, query_rest) = query.split_at(node_key.len());
+                        (trie, key, query) = (&mut node.trie, Some(node_key), query_rest);
+                        continue;
+                    }
+
+                    match order {
+                        Ordering::Less => idx + 1,
+                        _ => idx,
+                    }
+                }
+            };
+
+            iter.states.push_front(IterStateMut {
+                key,
+                value: None,
+                nodes: &mut trie.nodes[idx..],
+            });
+            break;
+        }
+
+        iter.front_depth = iter.states.len() - 1;
+        iter
+    }
+
+    /// Mutable version of `Iter::seek_end`
+    fn seek_end(&mut self, end: Bound<&P::Ref>) {
+        let (mut query, included) = match end {
+            Bound::Unbounded => return,
+            Bound::Included(query) => (query, true),
+            Bound::Excluded(query) => (query, false),
+        };
+
+        loop {
+            let shared = self.states.len() == self.front_depth + 1;
+            let Some(state) = self.states.back_mut() else {
+                return;
+            };
+
+            if query.is_empty() {
+                state.nodes = &mut [];
+                if !included {
+                    state.value = None;
+                }
+                if shared && self.front_depth > 0 {
+                    self.states.clear();
+                }
+                return;
+            }
+
+            let nodes = mem::take(&mut state.nodes);
+            let idx = match nodes
+                .binary_search_by(|node| P::Ref::cmp_first(node.key.borrow(), query))
+            {
+                Err(idx) => idx,
+                Ok(idx) => {
+                    let (_, node_rest, query_rest) = P::Ref::prefix(nodes[idx].key.borrow(), query);
+                    let order = P::Ref::cmp_first(node_rest, query_rest);
+                    if node_rest.is_empty() {
+                        let (before, rest) = nodes.split_at_mut(idx);
+                        state.nodes = before;
+                        let node = &mut rest[0];
+                        let node_key: &'a P::Ref = node.key.borrow();
+                        self.back_key.append_ref(node_key);
+                        self.states
+                            .push_back(IterStateMut::new(Some(node_key), &mut node.trie));
+                        (_, query) = query.split_at(node_key.len());
+                        continue;
+                    }
+
+                    match order {
+                        Ordering::Less => idx + 1,
+                        _ => idx,
+                    }
+                }
+            };
+            state.nodes = &mut nodes[..idx];
+            if idx > 0 || !shared || self.front_depth == 0 {
+                return;
+            }
+
+            self.states.pop_back();
+            self.front_depth -= 1;
+            let child_key = self.states.back().and_then(|state| state.key).unwrap();
+            self.back_key.append_ref(child_key);
+            let (_, child_rest, query_rest) = P::Ref::prefix(child_key, query);
+            if !child_rest.is_empty() {
+                if P::Ref::cmp_first(child_rest, query_rest) != Ordering::Less {
+                    self.states.clear();
+                }
+                return;
+            }
+            query = query_rest;
+        }
+    }
+
+    /// Call `f` with each remaining key and value. Keys are borrowed from a
+    /// buffer owned by the iterator, so unlike [Iterator::for_each], no key
+    /// is allocated.
+    pub fn for_each_with_key<F>(mut self, mut f: F)
+    where
+        F: FnMut(&P::Ref, &'a mut V),
+    {
+        while let Some((key, value)) = self.next_with_key() {
+            f(key, value);
+        }
+    }
+
+    /// Next key and value, with the key borrowed from `self.front_key`
+    pub(super) fn next_with_key(&mut self) -> Option<(&P::Ref, &'a mut V)> {
+        loop {
+            match self.states.front_mut()?.next() {
+                Some(IterStateItemMut::Value(value)) => {
+                    return Some((self.front_key.borrow(), value))
+                }
+                Some(IterStateItemMut::Trie(key, trie)) => {
+                    self.front_key.append_ref(key);
+                    self.states.push_front(IterStateMut::new(Some(key), trie));
+                    self.front_depth += 1;
+                }
+                None => {
+                    let state = self.states.pop_front()?;
+                    if self.front_depth > 0 {
+                        self.front_depth -= 1;
+                        let key_len = self.front_key.borrow().len() - state.key.unwrap().len();
+                        self.front_key.truncate(key_len);
+                    } else if let Some(next) = self.states.front() {
+                        self.front_key.append_ref(next.key.unwrap());
+                    }
+                }
+            }
+        }
+    }
+
+    /// Next key and value from the back, with the key borrowed from
+    /// `self.back_key`
+    pub(super) fn next_back_with_key(&mut self) -> Option<(&P::Ref, &'a mut V)> {
+        loop {
+            match self.states.back_mut()?.next_back() {
+                Some(IterStateItemMut::Value(value)) => {
+                    return Some((self.back_key.borrow(), value))
+                }
+                Some(IterStateItemMut::Trie(key, trie)) => {
+                    self.back_key.append_ref(key);
+                    self.states.push_back(IterStateMut::new(Some(key), trie));
+                }
+                None => {
+                    let state = self.states.pop_back()?;
+                    if self.states.len() > self.front_depth {
+                        let key_len = self.back_key.borrow().len() - state.key.unwrap().len();
+                        self.back_key.truncate(key_len);
+                    } else if let Some(next) = self.states.back() {
+                        self.front_depth -= 1;
+                        self.back_key.append_ref(next.key.unwrap());
+                    }
+                }
+            }
         }
     }
 }
@@ -46,16 +272,47 @@ where
     type Item = (P, &'a mut V);
 
     fn next(&mut self) -> Option<Self::Item> {
+        self.next_with_key()
+            .map(|(key, value)| (key.to_owned(), value))
+    }
+}
+
+impl<'a, P, V> DoubleEndedIterator for IterMut<'a, P, V>
+where
+    P: Key,
+{
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.next_back_with_key()
+            .map(|(key, value)| (key.to_owned(), value))
+    }
+}
+
+/// Mutable iterator over the values of a [RadixTrie](super::RadixTrie), in
+/// lexicographic order of their keys. Keys are never built.
+pub struct ValuesMut<'a, P, V>(Vec<IterStateMut<'a, P, V>>)
+where
+    P: Key;
+
+impl<'a, P, V> ValuesMut<'a, P, V>
+where
+    P: Key,
+{
+    pub(super) fn new(tree: &'a mut Trie<P, V>) -> Self {
+        ValuesMut(vec![IterStateMut::new(None, tree)])
+    }
+}
+
+impl<'a, P, V> Iterator for ValuesMut<'a, P, V>
+where
+    P: Key,
+{
+    type Item = &'a mut V;
+
+    fn next(&mut self) -> Option<&'a mut V> {
         loop {
             match self.0.last_mut().and_then(|node_it| node_it.next()) {
-                Some(IterStateItemMut::Value(value)) => {
-                    let mut key_iter = self.0.iter().filter_map(|e| e.key);
-                    let key = P::Ref::concat(&mut key_iter);
-                    return Some((key, value));
-                }
-                Some(IterStateItemMut::Trie(key, trie)) => {
-                    self.0.push(Self::to_iter_state(Some(key), trie))
-                }
+                Some(IterStateItemMut::Value(value)) => return Some(value),
+                Some(IterStateItemMut::Trie(_, trie)) => self.0.push(IterStateMut::new(None, trie)),
                 None => {
                     self.0.pop()?;
                 }
@@ -64,12 +321,33 @@ where
     }
 }
 
-pub enum IterStateItemMut<'a, P, V>
+enum IterStateItemMut<'a, P, V>
 where
     P: Key,
 {
     Value(&'a mut V),
-    Trie(&'a P::Ref, &'a mut RadixTrie<P, V>),
+    Trie(&'a P::Ref, &'a mut Trie<P, V>),
+}
+
+impl<'a, P, V> IterStateMut<'a, P, V>
+where
+    P: Key,
+{
+    fn new(key: Option<&'a P::Ref>, tree: &'a mut Trie<P, V>) -> Self {
+        IterStateMut {
+            key,
+            value: tree.value.as_mut(),
+            nodes: &mut tree.nodes[..],
+        }
+    }
+
+    fn next_back(&mut self) -> Option<IterStateItemMut<'a, P, V>> {
+        if let Some((last, rest)) = mem::take(&mut self.nodes).split_last_mut() {
+            self.nodes = rest;
+            return Some(IterStateItemMut::Trie(last.key.borrow(), &mut last.trie));
+        }
+        self.value.take().map(IterStateItemMut::Value)
+    }
 }
 
 impl<'a, P, V> Iterator for IterStateMut<'a, P, V>
@@ -82,10 +360,21 @@ where
         if let Some(value) = self.value.take() {
             return Some(IterStateItemMut::Value(value));
         }
-        let nodes = self.nodes.take()?;
-        let (head, rest) = nodes.split_first_mut()?;
-        self.nodes = Some(rest);
-        return Some(IterStateItemMut::Trie(head.key.borrow(), &mut head.trie));
+        let (head, rest) = mem::take(&mut self.nodes).split_first_mut()?;
+        self.nodes = rest;
+        Some(IterStateItemMut::Trie(head.key.borrow(), &mut head.trie))
+    }
+}
+
+impl<'a, P, V> IntoIterator for &'a mut RadixTrie<P, V>
+where
+    P: Key,
+{
+    type Item = (P, &'a mut V);
+    type IntoIter = IterMut<'a, P, V>;
+
+    fn into_iter(self) -> IterMut<'a, P, V> {
+        self.iter_mut()
     }
 }
 
@@ -113,9 +402,90 @@ mod test {
                 ("ab".into(), &2),
                 ("abc".into(), &3),
                 ("abd".into(), &4),
-            ]
-            .into_iter(),
+            ],
             trie.iter(),
         );
     }
+
+    #[test]
+    fn test_iter_prefix_mut() {
+        let mut trie = RadixTrie::<String, _>::new();
+        trie.insert("ab".into(), 1);
+        trie.insert("abc".into(), 2);
+        trie.insert("abd".into(), 3);
+        trie.insert("b".into(), 4);
+
+        for (_, elem) in trie.iter_prefix_mut("abc") {
+            *elem += 10;
+        }
+        for (_, elem) in trie.iter_prefix_mut("a") {
+            *elem += 1;
+        }
+        assert_eq!(0, trie.iter_prefix_mut("ac").count());
+
+        assert_equal(
+            vec![
+                ("ab".into(), &2),
+                ("abc".into(), &13),
+                ("abd".into(), &4),
+                ("b".into(), &4),
+            ],
+            trie.iter(),
+        );
+    }
+
+    #[test]
+    fn test_for_each_with_key_mut() {
+        let mut trie = RadixTrie::<String, _>::new();
+        trie.insert("ab".into(), 0);
+        trie.insert("abc".into(), 0);
+        trie.insert("b".into(), 0);
+
+        trie.for_each_with_key_mut(|key, value| *value = key.len());
+        trie.iter_prefix_mut("abc")
+            .for_each_with_key(|key, value| *value += key.len());
+
+        assert_equal(
+            vec![("ab".into(), &2), ("abc".into(), &6), ("b".into(), &1)],
+            trie.iter(),
+        );
+    }
+
+    #[test]
+    fn test_values_mut() {
+        let mut trie = RadixTrie::<String, _>::new();
+        trie.insert("ab".into(), 1);
+        trie.insert("abc".into(), 2);
+        trie.insert("".into(), 3);
+
+        for value in trie.values_mut() {
+            *value *= 10;
+        }
+        assert_equal(
+            vec![("".into(), &30), ("ab".into(), &10), ("abc".into(), &20)],
+            trie.iter(),
+        );
+    }
+
+    #[test]
+    fn test_double_ended() {
+        let mut trie = RadixTrie::<String, _>::new();
+        for key in ["", "ab", "abc", "abd", "b"] {
+            trie.insert(key.into(), 0);
+        }
+
+        for (idx, (_, value)) in trie.iter_mut().rev().enumerate() {
+            *value = idx;
+        }
+        assert_equal(vec![&4, &3, &2, &1, &0], trie.values());
+
+        let mut iter = trie.iter_mut();
+        *iter.next_back().unwrap().1 += 10;
+        *iter.next().unwrap().1 += 10;
+        *iter.next_back().unwrap().1 += 10;
+        *iter.next().unwrap().1 += 10;
+        *iter.next().unwrap().1 += 10;
+        assert!(iter.next_back().is_none());
+        assert_equal(vec![&14, &13, &12, &11, &10], trie.values());
+    }
 }
@@ -1,4 +1,4 @@
-use std::borrow::Borrow;
+use std::{borrow::Borrow, cmp::Ordering};
 
 /// Trait that the key for a RadixTrie must implement
 /// `Ref` is the "reference" type of the key, for String this would be &str,
@@ -9,6 +9,14 @@ pub trait Key: Borrow<Self::Ref> {
 
     /// Concatenate this key with another
     fn concat(self, other: Self) -> Self;
+
+    /// Append `other` to the end of this key, in place
+    fn append_ref(&mut self, other: &Self::Ref);
+
+    /// Shorten this key to its first `len` units (see [KeyRef::len]), in
+    /// place. `len` is always the length of a prefix of this key that was
+    /// built with `append_ref`.
+    fn truncate(&mut self, len: usize);
 }
 
 /// Trait that the key's Ref type must implement.
@@ -18,10 +26,42 @@ pub trait KeyRef<Key: ?Sized>: ToOwned<Owned = Key> {
     /// Is the key empty?
     fn is_empty(&self) -> bool;
 
+    /// Length of the key, in units (e.g. bytes of a `str`)
+    fn len(&self) -> usize;
+
+    /// Split the key into its first `mid` units, and the units that remain.
+    /// `mid` is always a length that `prefix` produced for this key.
+    fn split_at(&self, mid: usize) -> (&Self, &Self);
+
+    /// Split the first unit off of the key, or `None` if the key is empty.
+    /// Keys whose units are not all of length 1 (such as the chars of a
+    /// `str`, measured in bytes) must override this.
+    fn split_first(&self) -> Option<(&Self, &Self)> {
+        if self.is_empty() {
+            return None;
+        }
+        Some(self.split_at(1))
+    }
+
     /// For keys `a` and `b`, return the common prefix between the two, and the
     /// remaining parts of the keys that remain
     fn prefix<'a>(a: &'a Self, b: &'a Self) -> (&'a Self, &'a Self, &'a Self);
 
+    /// Compare the first unit (e.g. the first `char` of a `str`) of keys `a`
+    /// and `b`. An empty key orders before any non-empty key.
+    ///
+    /// Sibling nodes never share a first unit, so this is enough to keep the
+    /// children of a node ordered, to find the child to descend into with a
+    /// binary search, and for iteration to yield keys in lexicographic order.
+    fn cmp_first(a: &Self, b: &Self) -> Ordering;
+
+    /// Compare keys `a` and `b` in lexicographic order of their units, the
+    /// order in which a trie is iterated
+    fn cmp_keys(a: &Self, b: &Self) -> Ordering {
+        let (_, a_rest, b_rest) = Self::prefix(a, b);
+        Self::cmp_first(a_rest, b_rest)
+    }
+
     /// Concatenate an iterator of Self into the owned type
     fn concat(iter: &mut dyn Iterator<Item = &Self>) -> Key;
 }
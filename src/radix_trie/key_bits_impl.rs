@@ -0,0 +1,313 @@
+use std::{
+    borrow::Borrow,
+    cmp::Ordering,
+    fmt::{Debug, Display},
+    ops::Deref,
+};
+
+use super::key::{Key, KeyRef};
+
+/// Key made of a string of bits, for tries where prefixes do not fall on
+/// byte boundaries (e.g. IP prefix tables, see [super::ip_net]).
+///
+/// Bits are stored one per byte, so that a key can be borrowed (as [Bits])
+/// starting at any bit. [BitKey::from_bytes] and [Bits::to_bytes] convert
+/// from and to the packed, most significant bit first representation.
+#[derive(Clone, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct BitKey(Vec<bool>);
+
+/// Borrowed string of bits, the [KeyRef] type of [BitKey]
+#[derive(PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[repr(transparent)]
+pub struct Bits([bool]);
+
+impl BitKey {
+    /// Create an empty key
+    pub fn new() -> BitKey {
+        BitKey(vec![])
+    }
+
+    /// Create a key from the first `bit_len` bits of `bytes`, most
+    /// significant bit first. Panics if `bytes` holds fewer than `bit_len`
+    /// bits.
+    pub fn from_bytes(bytes: &[u8], bit_len: usize) -> BitKey {
+        assert!(
+            bit_len <= bytes.len() * 8,
+            "bit_len {} out of range for {} bytes",
+            bit_len,
+            bytes.len()
+        );
+        BitKey(
+            (0..bit_len)
+                .map(|idx| bytes[idx / 8] & (0x80 >> (idx % 8)) != 0)
+                .collect(),
+        )
+    }
+
+    /// Append a bit to the end of the key
+    pub fn push(&mut self, bit: bool) {
+        self.0.push(bit);
+    }
+}
+
+impl Bits {
+    fn new(bits: &[bool]) -> &Bits {
+        // SAFETY: Bits is a repr(transparent) wrapper around [bool], so the
+        // two have the same layout and pointer metadata, and the reference
+        // keeps the lifetime and borrow of `bits`
+        unsafe { &*(bits as *const [bool] as *const Bits) }
+    }
+
+    /// Iterate over the bits
+    pub fn iter(&self) -> impl Iterator<Item = bool> + '_ {
+        self.0.iter().copied()
+    }
+
+    /// Pack the bits into bytes, most significant bit first. The last byte
+    /// is padded with zero bits.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.0
+            .chunks(8)
+            .map(|chunk| {
+                chunk
+                    .iter()
+                    .enumerate()
+                    .fold(0, |byte, (idx, &bit)| byte | ((bit as u8) << (7 - idx)))
+            })
+            .collect()
+    }
+}
+
+impl Deref for BitKey {
+    type Target = Bits;
+
+    fn deref(&self) -> &Bits {
+        self.borrow()
+    }
+}
+
+impl Borrow<Bits> for BitKey {
+    fn borrow(&self) -> &Bits {
+        Bits::new(&self.0)
+    }
+}
+
+impl ToOwned for Bits {
+    type Owned = BitKey;
+
+    fn to_owned(&self) -> BitKey {
+        BitKey(self.0.to_vec())
+    }
+}
+
+impl FromIterator<bool> for BitKey {
+    fn from_iter<T: IntoIterator<Item = bool>>(iter: T) -> Self {
+        BitKey(iter.into_iter().collect())
+    }
+}
+
+impl Display for Bits {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for bit in self.iter() {
+            f.write_str(if bit { "1" } else { "0" })?;
+        }
+        Ok(())
+    }
+}
+
+impl Debug for Bits {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Bits({})", self)
+    }
+}
+
+impl Display for BitKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        Display::fmt(self.deref(), f)
+    }
+}
+
+impl Debug for BitKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "BitKey({})", self)
+    }
+}
+
+/* Bit string key type - a BitKey / &Bits */
+impl Key for BitKey {
+    type Ref = Bits;
+
+    fn concat(mut self, other: Self) -> Self {
+        self.0.extend(other.0);
+        self
+    }
+
+    fn append_ref(&mut self, other: &Bits) {
+        self.0.extend_from_slice(&other.0);
+    }
+
+    fn truncate(&mut self, len: usize) {
+        self.0.truncate(len);
+    }
+}
+
+impl KeyRef<BitKey> for Bits {
+    fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    fn split_at(&self, mid: usize) -> (&Bits, &Bits) {
+        let (a, b) = self.0.split_at(mid);
+        (Bits::new(a), Bits::new(b))
+    }
+
+    fn prefix<'a>(a: &'a Bits, b: &'a Bits) -> (&'a Bits, &'a Bits, &'a Bits) {
+        let prefix_len = a.iter().zip(b.iter()).take_while(|(a, b)| a == b).count();
+        let (prefix, a_rest) = a.split_at(prefix_len);
+        let (_, b_rest) = b.split_at(prefix_len);
+        (prefix, a_rest, b_rest)
+    }
+
+    fn cmp_first(a: &Bits, b: &Bits) -> Ordering {
+        a.0.first().cmp(&b.0.first())
+    }
+
+    fn concat(iter: &mut dyn Iterator<Item = &Bits>) -> BitKey {
+        iter.flat_map(Bits::iter).collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::cmp::Ordering;
+
+    use itertools::assert_equal;
+
+    use super::BitKey;
+    use crate::radix_trie::{
+        key::{Key, KeyRef},
+        RadixTrie,
+    };
+
+    fn bits(s: &str) -> BitKey {
+        s.chars().map(|c| c == '1').collect()
+    }
+
+    #[test]
+    fn test_works() {
+        assert_eq!(
+            (&*bits(""), &*bits(""), &*bits("")),
+            KeyRef::prefix(&*bits(""), &bits(""))
+        );
+        assert_eq!(
+            (&*bits(""), &*bits("0"), &*bits("1")),
+            KeyRef::prefix(&*bits("0"), &bits("1"))
+        );
+        assert_eq!(
+            (&*bits("101"), &*bits("0"), &*bits("11")),
+            KeyRef::prefix(&*bits("1010"), &bits("10111"))
+        );
+        assert_eq!(
+            (&*bits("11"), &*bits(""), &*bits("")),
+            KeyRef::prefix(&*bits("11"), &bits("11"))
+        );
+    }
+
+    #[test]
+    fn test_unaligned() {
+        let a = bits("1100101011110000101");
+        let b = bits("011000101011111");
+
+        // views that start partway through a byte
+        let (_, a_rest) = a.split_at(3);
+        let (_, b_rest) = b.split_at(5);
+        assert_eq!(bits("0101011110000101"), a_rest.to_owned());
+        assert_eq!(bits("0101011111"), b_rest.to_owned());
+        assert_eq!(vec![0b0101_0111, 0b1000_0101], a_rest.to_bytes());
+
+        let (prefix, a_rest, b_rest) = KeyRef::prefix(a_rest, b_rest);
+        assert_eq!(bits("010101111"), prefix.to_owned());
+        assert_eq!(bits("0000101"), a_rest.to_owned());
+        assert_eq!(bits("1"), b_rest.to_owned());
+        assert_eq!(Ordering::Less, a_rest.cmp(b_rest));
+
+        // append and truncate across byte boundaries
+        let mut key = bits("101");
+        key.append_ref(a_rest);
+        key.append_ref(prefix);
+        assert_eq!(bits("1010000101010101111"), key);
+        key.truncate(7);
+        assert_eq!(bits("1010000"), key);
+        assert_eq!(vec![0b1010_0000], key.to_bytes());
+        assert_eq!(bits("10100001"), key.concat(bits("1")));
+    }
+
+    #[test]
+    fn test_cmp_first() {
+        assert_eq!(Ordering::Equal, KeyRef::cmp_first(&*bits(""), &bits("")));
+        assert_eq!(Ordering::Less, KeyRef::cmp_first(&*bits(""), &bits("0")));
+        assert_eq!(Ordering::Less, KeyRef::cmp_first(&*bits("01"), &bits("1")));
+        assert_eq!(
+            Ordering::Equal,
+            KeyRef::cmp_first(&*bits("10"), &bits("11"))
+        );
+    }
+
+    #[test]
+    fn test_ord() {
+        let keys = [
+            "",
+            "0",
+            "00",
+            "000000001",
+            "01",
+            "1",
+            "10",
+            "100000000",
+            "11",
+        ];
+        for (idx, a) in keys.iter().enumerate() {
+            for (jdx, b) in keys.iter().enumerate() {
+                assert_eq!(idx.cmp(&jdx), bits(a).cmp(&bits(b)), "{a:?} {b:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_bytes() {
+        let key = BitKey::from_bytes(&[0b1010_0000, 0xff], 12);
+        assert_eq!(bits("101000001111"), key);
+        assert_eq!(vec![0b1010_0000, 0xf0], key.to_bytes());
+        assert_eq!(BitKey::new(), BitKey::from_bytes(&[], 0));
+        assert_eq!("BitKey(101000001111)", format!("{:?}", key));
+    }
+
+    #[test]
+    fn test_trie() {
+        let mut trie: RadixTrie<BitKey, usize> = RadixTrie::new();
+        trie.insert(bits("1"), 1);
+        trie.insert(bits("101"), 2);
+        trie.insert(bits("0"), 3);
+        trie.insert(bits("1011"), 4);
+
+        assert_eq!(Some(&2), trie.get(&*bits("101")));
+        assert_eq!(None, trie.get(&*bits("10")));
+        assert_eq!(
+            Some((&*bits("101"), &2)),
+            trie.longest_prefix(&*bits("1010"))
+        );
+        assert_equal(
+            vec![
+                (bits("0"), &3),
+                (bits("1"), &1),
+                (bits("101"), &2),
+                (bits("1011"), &4),
+            ],
+            trie.iter(),
+        );
+    }
+}
@@ -0,0 +1,231 @@
+use std::{
+    cmp::Ordering,
+    path::{Components, Path, PathBuf},
+};
+
+use super::key::{Key, KeyRef};
+
+/* Filesystem path key type - a PathBuf / &Path. Keys are split on path
+ * component boundaries only, so e.g. `/usr/lib` and `/usr/libexec` share the
+ * prefix `/usr`, and not `/usr/lib`. */
+impl Key for PathBuf {
+    type Ref = Path;
+
+    fn concat(mut self, other: Self) -> Self {
+        self.append_ref(&other);
+        self
+    }
+
+    // `PathBuf::push` replaces the whole path with an absolute one. Only the
+    // first component of a key can be a root, so only the first part of a
+    // key that is put back together can be absolute.
+    fn append_ref(&mut self, other: &Path) {
+        debug_assert!(!other.has_root() || self.as_os_str().is_empty());
+        if !other.as_os_str().is_empty() {
+            self.push(other);
+        }
+    }
+
+    fn truncate(&mut self, len: usize) {
+        if len == 0 {
+            // the root of an absolute path can not be popped
+            self.as_mut_os_string().clear();
+            return;
+        }
+        for _ in len..self.components().count() {
+            self.pop();
+        }
+    }
+}
+
+impl KeyRef<PathBuf> for Path {
+    fn is_empty(&self) -> bool {
+        self.as_os_str().is_empty()
+    }
+
+    fn len(&self) -> usize {
+        self.components().count()
+    }
+
+    fn split_at(&self, mid: usize) -> (&Path, &Path) {
+        let mut rest = self.components();
+        for _ in 0..mid {
+            rest.next();
+        }
+        split_components(self, rest)
+    }
+
+    fn prefix<'a>(a: &'a Path, b: &'a Path) -> (&'a Path, &'a Path, &'a Path) {
+        let (mut a_rest, mut b_rest) = (a.components(), b.components());
+        loop {
+            let (mut a_next, mut b_next) = (a_rest.clone(), b_rest.clone());
+            match (a_next.next(), b_next.next()) {
+                (Some(a_unit), Some(b_unit)) if a_unit == b_unit => {
+                    (a_rest, b_rest) = (a_next, b_next);
+                }
+                _ => break,
+            }
+        }
+
+        let (prefix, a_rest) = split_components(a, a_rest);
+        (prefix, a_rest, b_rest.as_path())
+    }
+
+    fn cmp_first(a: &Path, b: &Path) -> Ordering {
+        a.components().next().cmp(&b.components().next())
+    }
+
+    fn concat(iter: &mut dyn Iterator<Item = &Path>) -> PathBuf {
+        iter.fold(PathBuf::new(), |path, part| path.concat(part.to_owned()))
+    }
+}
+
+/// Split `path` in two where `rest`, its components with some taken from the
+/// front, begins. Takes time linear in the length of `path`.
+fn split_components<'a>(path: &'a Path, rest: Components<'a>) -> (&'a Path, &'a Path) {
+    // the head is what remains after taking as many components from the
+    // back, so that an absolute path keeps its root
+    let mut head = path.components();
+    for _ in rest.clone() {
+        head.next_back();
+    }
+    (head.as_path(), rest.as_path())
+}
+
+#[cfg(test)]
+mod test {
+    use std::{
+        cmp::Ordering,
+        path::{Path, PathBuf},
+    };
+
+    use itertools::assert_equal;
+
+    use crate::radix_trie::{
+        key::{Key, KeyRef},
+        RadixTrie,
+    };
+
+    fn p(path: &str) -> &Path {
+        Path::new(path)
+    }
+
+    #[test]
+    fn test_works() {
+        assert_eq!((p(""), p(""), p("")), KeyRef::prefix(p(""), p("")));
+        assert_eq!((p(""), p("a"), p("b")), KeyRef::prefix(p("a"), p("b")));
+        assert_eq!((p("a"), p(""), p("b")), KeyRef::prefix(p("a"), p("a/b")));
+        assert_eq!((p("a/b"), p(""), p("")), KeyRef::prefix(p("a/b"), p("a/b")));
+        assert_eq!(
+            (p("/usr"), p("lib"), p("libexec")),
+            KeyRef::prefix(p("/usr/lib"), p("/usr/libexec"))
+        );
+        assert_eq!(
+            (p("/"), p("usr"), p("etc")),
+            KeyRef::prefix(p("/usr"), p("/etc"))
+        );
+        assert_eq!(
+            (p(""), p("/usr"), p("usr")),
+            KeyRef::prefix(p("/usr"), p("usr"))
+        );
+    }
+
+    #[test]
+    fn test_split_at() {
+        assert_eq!(3, KeyRef::len(p("/usr/lib")));
+        assert_eq!((p(""), p("/usr/lib")), KeyRef::split_at(p("/usr/lib"), 0));
+        assert_eq!((p("/"), p("usr/lib")), KeyRef::split_at(p("/usr/lib"), 1));
+        assert_eq!((p("/usr"), p("lib")), KeyRef::split_at(p("/usr/lib"), 2));
+        assert_eq!((p("a"), p("b/c")), KeyRef::split_at(p("a/b/c"), 1));
+        assert_eq!((p("a/b/c"), p("")), KeyRef::split_at(p("a/b/c"), 3));
+        assert_eq!((p("./a"), p("b")), KeyRef::split_at(p("./a//b/"), 2));
+    }
+
+    #[test]
+    fn test_deep_paths() {
+        // long edges, which are split near their start
+        let deep = |first: usize| -> PathBuf {
+            (0..2_000)
+                .map(|idx| if idx == 2 { first } else { idx })
+                .map(|idx| idx.to_string())
+                .collect()
+        };
+        let mut trie: RadixTrie<PathBuf, usize> = RadixTrie::new();
+        for first in 0..50 {
+            trie.insert(deep(first), first);
+        }
+        assert_eq!(50, trie.len());
+        for first in 0..50 {
+            assert_eq!(Some(&first), trie.get(&deep(first)));
+        }
+
+        let (a, b) = (deep(1), deep(2));
+        let (prefix, a_rest, b_rest) = KeyRef::prefix(&*a, &b);
+        assert_eq!(p("0/1"), prefix);
+        assert_eq!(1_998, KeyRef::len(a_rest));
+        assert_eq!(1_998, KeyRef::len(b_rest));
+    }
+
+    #[test]
+    fn test_append_and_truncate() {
+        let mut key = PathBuf::new();
+        key.append_ref(p("/usr"));
+        key.append_ref(p(""));
+        key.append_ref(p("lib/x86_64"));
+        assert_eq!(p("/usr/lib/x86_64"), key);
+
+        Key::truncate(&mut key, 2);
+        assert_eq!(p("/usr"), key);
+        Key::truncate(&mut key, 1);
+        assert_eq!(p("/"), key);
+        Key::truncate(&mut key, 0);
+        assert_eq!(p(""), key);
+    }
+
+    #[test]
+    fn test_cmp_first() {
+        assert_eq!(Ordering::Equal, KeyRef::cmp_first(p(""), p("")));
+        assert_eq!(Ordering::Less, KeyRef::cmp_first(p(""), p("a")));
+        assert_eq!(Ordering::Less, KeyRef::cmp_first(p("lib"), p("libexec")));
+        assert_eq!(Ordering::Equal, KeyRef::cmp_first(p("lib/a"), p("lib/b")));
+    }
+
+    #[test]
+    fn test_trie() {
+        let mut trie: RadixTrie<std::path::PathBuf, usize> = RadixTrie::new();
+        trie.insert("/usr/lib".into(), 1);
+        trie.insert("/usr/libexec".into(), 2);
+        trie.insert("/usr/lib/x86_64".into(), 3);
+        trie.insert("/etc".into(), 4);
+
+        assert_eq!(Some(&1), trie.get(p("/usr/lib")));
+        assert_eq!(None, trie.get(p("/usr/li")));
+        assert_eq!(None, trie.get(p("/usr")));
+        assert_eq!(
+            Some((p("/usr/lib"), &1)),
+            trie.longest_prefix(p("/usr/lib/python3"))
+        );
+        assert_eq!(None, trie.longest_prefix(p("/usr/libx")));
+
+        assert_equal(
+            vec![
+                ("/etc".into(), &4),
+                ("/usr/lib".into(), &1),
+                ("/usr/lib/x86_64".into(), &3),
+                ("/usr/libexec".into(), &2),
+            ],
+            trie.iter(),
+        );
+        assert_equal(
+            vec![("/usr/lib".into(), &1), ("/usr/lib/x86_64".into(), &3)],
+            trie.iter_prefix(p("/usr/lib")),
+        );
+
+        assert_eq!(Some(4), trie.remove(p("/etc")));
+        assert_eq!(Some(1), trie.remove(p("/usr/lib")));
+        assert_equal(
+            vec![("/usr/lib/x86_64".into(), &3), ("/usr/libexec".into(), &2)],
+            trie.iter(),
+        );
+    }
+}
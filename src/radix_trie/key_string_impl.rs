@@ -1,3 +1,5 @@
+use std::cmp::Ordering;
+
 use itertools::Itertools;
 
 use super::key::{Key, KeyRef};
@@ -9,6 +11,14 @@ impl Key for String {
     fn concat(self, other: Self) -> Self {
         self + &other
     }
+
+    fn append_ref(&mut self, other: &str) {
+        self.push_str(other);
+    }
+
+    fn truncate(&mut self, len: usize) {
+        String::truncate(self, len);
+    }
 }
 
 impl KeyRef<String> for str {
@@ -16,6 +26,19 @@ impl KeyRef<String> for str {
         self.is_empty()
     }
 
+    fn len(&self) -> usize {
+        self.len()
+    }
+
+    fn split_at(&self, mid: usize) -> (&str, &str) {
+        self.split_at(mid)
+    }
+
+    fn split_first(&self) -> Option<(&str, &str)> {
+        let first = self.chars().next()?;
+        Some(self.split_at(first.len_utf8()))
+    }
+
     fn prefix<'a>(a: &'a str, b: &'a str) -> (&'a str, &'a str, &'a str) {
         let mut prefix_len = a.bytes().zip(b.bytes()).take_while(|(a, b)| a == b).count();
 
@@ -26,6 +49,10 @@ impl KeyRef<String> for str {
         (&a[..prefix_len], &a[prefix_len..], &b[prefix_len..])
     }
 
+    fn cmp_first(a: &str, b: &str) -> Ordering {
+        a.chars().next().cmp(&b.chars().next())
+    }
+
     fn concat(iter: &mut dyn Iterator<Item = &str>) -> String {
         iter.join("")
     }
@@ -33,6 +60,8 @@ impl KeyRef<String> for str {
 
 #[cfg(test)]
 mod test {
+    use std::cmp::Ordering;
+
     use crate::radix_trie::key::KeyRef;
 
     #[test]
@@ -43,4 +72,37 @@ mod test {
         assert_eq!(("ab", "", ""), KeyRef::prefix("ab", "ab"));
         assert_eq!(("foo", "123", "456"), KeyRef::prefix("foo123", "foo456"));
     }
+
+    #[test]
+    fn test_split_at() {
+        assert_eq!(("", ""), KeyRef::split_at("", 0));
+        assert_eq!(("ab", "c"), KeyRef::split_at("abc", 2));
+        assert_eq!(("Ю", "a"), KeyRef::split_at("Юa", KeyRef::len("Ю")));
+    }
+
+    #[test]
+    fn test_split_first() {
+        assert_eq!(None, KeyRef::split_first(""));
+        assert_eq!(Some(("a", "bc")), KeyRef::split_first("abc"));
+        assert_eq!(Some(("Ю", "a")), KeyRef::split_first("Юa"));
+    }
+
+    #[test]
+    fn test_cmp_first() {
+        assert_eq!(Ordering::Equal, KeyRef::cmp_first("", ""));
+        assert_eq!(Ordering::Less, KeyRef::cmp_first("", "a"));
+        assert_eq!(Ordering::Less, KeyRef::cmp_first("ab", "b"));
+        assert_eq!(Ordering::Equal, KeyRef::cmp_first("ab", "ac"));
+        assert_eq!(Ordering::Less, KeyRef::cmp_first("Ю", "Я"));
+    }
+
+    #[test]
+    fn test_cmp_keys() {
+        assert_eq!(Ordering::Equal, KeyRef::cmp_keys("", ""));
+        assert_eq!(Ordering::Equal, KeyRef::cmp_keys("ab", "ab"));
+        assert_eq!(Ordering::Less, KeyRef::cmp_keys("a", "ab"));
+        assert_eq!(Ordering::Greater, KeyRef::cmp_keys("ab", "a"));
+        assert_eq!(Ordering::Less, KeyRef::cmp_keys("abc", "abd"));
+        assert_eq!(Ordering::Less, KeyRef::cmp_keys("aЮ", "aЯ"));
+    }
 }
@@ -0,0 +1,211 @@
+use std::cmp::Ordering;
+
+use super::key::{Key, KeyRef};
+
+/* Sequence key type - a Vec<T> / &[T], e.g. bytes, token IDs or path
+ * segments. Elements must be `Ord`, as child nodes are kept ordered by their
+ * first element. */
+impl<T> Key for Vec<T>
+where
+    T: Ord + Clone,
+{
+    type Ref = [T];
+
+    fn concat(mut self, other: Self) -> Self {
+        self.extend(other);
+        self
+    }
+
+    fn append_ref(&mut self, other: &[T]) {
+        self.extend_from_slice(other);
+    }
+
+    fn truncate(&mut self, len: usize) {
+        Vec::truncate(self, len);
+    }
+}
+
+impl<T> KeyRef<Vec<T>> for [T]
+where
+    T: Ord + Clone,
+{
+    fn is_empty(&self) -> bool {
+        self.is_empty()
+    }
+
+    fn len(&self) -> usize {
+        self.len()
+    }
+
+    fn split_at(&self, mid: usize) -> (&[T], &[T]) {
+        self.split_at(mid)
+    }
+
+    fn prefix<'a>(a: &'a [T], b: &'a [T]) -> (&'a [T], &'a [T], &'a [T]) {
+        let prefix_len = a.iter().zip(b).take_while(|(a, b)| a == b).count();
+        (&a[..prefix_len], &a[prefix_len..], &b[prefix_len..])
+    }
+
+    fn cmp_first(a: &[T], b: &[T]) -> Ordering {
+        a.first().cmp(&b.first())
+    }
+
+    fn concat(iter: &mut dyn Iterator<Item = &[T]>) -> Vec<T> {
+        iter.flatten().cloned().collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::cmp::Ordering;
+
+    use itertools::assert_equal;
+
+    use crate::radix_trie::{key::KeyRef, RadixTrie};
+
+    #[test]
+    fn test_works() {
+        let empty: &[u8] = &[];
+        assert_eq!((empty, empty, empty), KeyRef::prefix(empty, empty));
+        assert_eq!(
+            (empty, &b"a"[..], &b"b"[..]),
+            KeyRef::prefix(&b"a"[..], b"b")
+        );
+        assert_eq!(
+            (&b"a"[..], empty, &b"b"[..]),
+            KeyRef::prefix(&b"a"[..], b"ab")
+        );
+        assert_eq!(
+            (&b"ab"[..], empty, empty),
+            KeyRef::prefix(&b"ab"[..], b"ab")
+        );
+        assert_eq!(
+            (&[0xd0][..], &[0xae][..], &[0xaf][..]),
+            KeyRef::prefix(&[0xd0, 0xae][..], &[0xd0, 0xaf])
+        );
+    }
+
+    #[test]
+    fn test_cmp_first() {
+        let empty: &[u8] = &[];
+        assert_eq!(Ordering::Equal, KeyRef::cmp_first(empty, empty));
+        assert_eq!(Ordering::Less, KeyRef::cmp_first(empty, &[0][..]));
+        assert_eq!(Ordering::Less, KeyRef::cmp_first(&[0, 9][..], &[1]));
+        assert_eq!(Ordering::Equal, KeyRef::cmp_first(&[1, 2][..], &[1, 3]));
+        assert_eq!(Ordering::Greater, KeyRef::cmp_first(&[0xff][..], &[0x7f]));
+    }
+
+    #[test]
+    fn test_trie() {
+        let mut trie: RadixTrie<Vec<u8>, usize> = RadixTrie::new();
+        assert_eq!(None, trie.insert(vec![1, 2, 3], 1));
+        assert_eq!(None, trie.insert(vec![1, 2, 0xff], 2));
+        assert_eq!(None, trie.insert(vec![0xff], 3));
+        assert_eq!(None, trie.insert(vec![], 4));
+        assert_eq!(None, trie.insert(vec![1], 5));
+        assert_eq!(Some(5), trie.insert(vec![1], 6));
+
+        assert_eq!(Some(&1), trie.get(&[1, 2, 3][..]));
+        assert_eq!(Some(&2), trie.get(&vec![1, 2, 0xff]));
+        assert_eq!(None, trie.get(&[1, 2][..]));
+        assert_eq!(
+            Some((&[1, 2, 3][..], &1)),
+            trie.longest_prefix(&[1, 2, 3, 4][..])
+        );
+
+        assert_equal(
+            vec![
+                (vec![], &4),
+                (vec![1], &6),
+                (vec![1, 2, 3], &1),
+                (vec![1, 2, 0xff], &2),
+                (vec![0xff], &3),
+            ],
+            trie.iter(),
+        );
+
+        assert_eq!(Some(1), trie.remove(&[1, 2, 3][..]));
+        assert_eq!(None, trie.remove(&[1, 2, 3][..]));
+        assert_eq!(4, trie.len());
+        assert_equal(
+            vec![(vec![1], &6), (vec![1, 2, 0xff], &2)],
+            trie.iter_prefix(&[1][..]),
+        );
+    }
+
+    #[test]
+    fn test_token_ids() {
+        let mut trie: RadixTrie<Vec<u32>, &str> = RadixTrie::new();
+        trie.insert(vec![1000, 20, 3], "a");
+        trie.insert(vec![1000, 20], "b");
+        trie.insert(vec![1000, 7], "c");
+        trie.insert(vec![5], "d");
+
+        assert_eq!(Some(&"a"), trie.get(&[1000, 20, 3][..]));
+        assert_eq!(None, trie.get(&[1000][..]));
+        assert_equal(
+            vec![
+                (vec![5], &"d"),
+                (vec![1000, 7], &"c"),
+                (vec![1000, 20], &"b"),
+                (vec![1000, 20, 3], &"a"),
+            ],
+            trie.iter(),
+        );
+
+        assert_eq!(Some("b"), trie.remove(&[1000, 20][..]));
+        assert_equal(
+            vec![(vec![1000, 7], &"c"), (vec![1000, 20, 3], &"a")],
+            trie.iter_prefix(&[1000][..]),
+        );
+    }
+
+    #[test]
+    fn test_path_segments() {
+        let mut trie: RadixTrie<Vec<&'static str>, usize> = RadixTrie::new();
+        trie.insert(vec!["usr", "lib"], 1);
+        trie.insert(vec!["usr", "libexec"], 2);
+        trie.insert(vec!["usr"], 3);
+
+        assert_eq!(
+            Some((&["usr", "lib"][..], &1)),
+            trie.longest_prefix(&["usr", "lib", "python3"][..])
+        );
+        assert_equal(
+            vec![
+                (vec!["usr"], &3),
+                (vec!["usr", "lib"], &1),
+                (vec!["usr", "libexec"], &2),
+            ],
+            trie.iter(),
+        );
+    }
+
+    #[test]
+    fn test_enum_symbols() {
+        #[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+        enum Symbol {
+            Open,
+            Close,
+            Word(String),
+        }
+        use Symbol::*;
+
+        let mut trie: RadixTrie<Vec<Symbol>, usize> = RadixTrie::new();
+        trie.insert(vec![Open, Word("a".into()), Close], 1);
+        trie.insert(vec![Open, Close], 2);
+        trie.insert(vec![Word("b".into())], 3);
+
+        for (_, value) in trie.iter_prefix_mut(&[Open][..]) {
+            *value += 10;
+        }
+        assert_equal(
+            vec![
+                (vec![Open, Close], &12),
+                (vec![Open, Word("a".into()), Close], &11),
+                (vec![Word("b".into())], &3),
+            ],
+            trie.iter(),
+        );
+    }
+}
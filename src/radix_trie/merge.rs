@@ -0,0 +1,409 @@
+use std::{borrow::Borrow, cmp::Ordering, mem};
+
+use crate::radix_trie::{Key, KeyRef, Node, RadixTrie, Trie};
+
+/// How `Trie::merge` combines the entries of two tries
+struct Merge<F> {
+    // keep the entries whose key is only in `self`
+    keep_self: bool,
+    // move in the entries whose key is only in `other`
+    keep_other: bool,
+    // value for a key in both tries, or `None` to drop the key
+    both: F,
+    // number of keys in both tries
+    common: usize,
+    // number of keys in both tries that were kept
+    kept: usize,
+}
+
+impl<P, V> RadixTrie<P, V>
+where
+    P: Key,
+{
+    /// Move every entry of `other` into the trie. For a key in both tries,
+    /// the value is `f(value, other_value)`.
+    ///
+    /// The tries are walked together, and a subtree that is only in `other`
+    /// is moved over whole, rather than a key at a time.
+    pub fn union_with<F>(&mut self, other: RadixTrie<P, V>, mut f: F)
+    where
+        F: FnMut(V, V) -> V,
+    {
+        self.merge(other, true, true, |value, other_value| {
+            Some(f(value, other_value))
+        });
+    }
+
+    /// Keep only the keys that are also in `other`, with the value
+    /// `f(value, other_value)`
+    pub fn intersection_with<F>(&mut self, other: RadixTrie<P, V>, mut f: F)
+    where
+        F: FnMut(V, V) -> V,
+    {
+        self.merge(other, false, false, |value, other_value| {
+            Some(f(value, other_value))
+        });
+    }
+
+    /// Remove every key that is in `other`
+    pub fn difference(&mut self, other: RadixTrie<P, V>) {
+        self.merge(other, true, false, |_, _| None);
+    }
+
+    /// Remove every key that is in `other`, and move in the entries of
+    /// `other` whose key isn't in the trie
+    pub fn symmetric_difference(&mut self, other: RadixTrie<P, V>) {
+        self.merge(other, true, true, |_, _| None);
+    }
+
+    /// Move every entry of `other` into the trie, leaving `other` empty. For
+    /// a key in both tries, the value from `other` replaces the old one.
+    pub fn append(&mut self, other: &mut RadixTrie<P, V>) {
+        self.union_with(mem::take(other), |_, other_value| other_value);
+    }
+
+    /// Split the trie in two at `key`, returning a trie with every entry
+    /// whose key is at or after `key` in lexicographic order. Only the
+    /// nodes along `key` are split, the subtrees on either side of it are
+    /// moved whole.
+    pub fn split_off<Q>(&mut self, key: &Q) -> RadixTrie<P, V>
+    where
+        Q: Borrow<P::Ref> + ?Sized,
+    {
+        let root = self.root.split_off(key.borrow());
+        // the subtrees that were moved have to be walked to count them
+        let len = root.count_values();
+        self.len -= len;
+        self.check_invariants();
+
+        let other = RadixTrie { root, len };
+        other.check_invariants();
+        other
+    }
+
+    fn merge<F>(&mut self, other: RadixTrie<P, V>, keep_self: bool, keep_other: bool, both: F)
+    where
+        F: FnMut(V, V) -> Option<V>,
+    {
+        let mut merge = Merge {
+            keep_self,
+            keep_other,
+            both,
+            common: 0,
+            kept: 0,
+        };
+        self.root.merge(other.root, &mut merge);
+
+        // only the keys in both tries are visited, the rest are in subtrees
+        // that were kept or dropped whole
+        let mut len = merge.kept;
+        if keep_self {
+            len += self.len - merge.common;
+        }
+        if keep_other {
+            len += other.len - merge.common;
+        }
+        self.len = len;
+        self.check_invariants();
+    }
+}
+
+impl<P, V> Trie<P, V>
+where
+    P: Key,
+{
+    /// Merge `other`, which is at the same key as `self`, into `self`.
+    /// Children with the same first unit are merged, after splitting their
+    /// keys at the prefix they share, and the rest are kept or dropped whole.
+    fn merge<F>(&mut self, other: Trie<P, V>, merge: &mut Merge<F>)
+    where
+        F: FnMut(V, V) -> Option<V>,
+    {
+        self.value = match (self.value.take(), other.value) {
+            (Some(value), Some(other_value)) => {
+                merge.common += 1;
+                let value = (merge.both)(value, other_value);
+                merge.kept += value.is_some() as usize;
+                value
+            }
+            (value, None) => value.filter(|_| merge.keep_self),
+            (None, other_value) => other_value.filter(|_| merge.keep_other),
+        };
+
+        // both lists of children are ordered by their first unit, so they
+        // are walked together
+        let mut others = other.nodes.into_iter().peekable();
+        let mut nodes = Vec::with_capacity(self.nodes.len());
+        for node in mem::take(&mut self.nodes) {
+            let is_before = |other: &Node<P, V>| {
+                P::Ref::cmp_first(other.key.borrow(), node.key.borrow()) == Ordering::Less
+            };
+            while let Some(other_node) = others.next_if(is_before) {
+                if merge.keep_other {
+                    nodes.push(other_node);
+                }
+            }
+
+            let is_same = |other: &Node<P, V>| {
+                P::Ref::cmp_first(other.key.borrow(), node.key.borrow()) == Ordering::Equal
+            };
+            match others.next_if(is_same) {
+                Some(other_node) => nodes.extend(node.merge(other_node, merge)),
+                None if merge.keep_self => nodes.push(node),
+                None => {}
+            }
+        }
+        if merge.keep_other {
+            nodes.extend(others);
+        }
+        self.nodes = nodes;
+    }
+
+    /// Move the entries at or after `query` out of the trie, into a trie at
+    /// the same key as `self`
+    fn split_off(&mut self, query: &P::Ref) -> Trie<P, V> {
+        if query.is_empty() {
+            return mem::replace(self, Trie::new());
+        }
+
+        // the children from `idx` on are after `query`
+        let idx = match self.child_idx(query) {
+            Err(idx) => idx,
+            Ok(idx) => {
+                let node = &mut self.nodes[idx];
+                let (_, node_rest, query_rest) = P::Ref::prefix(node.key.borrow(), query);
+                if node_rest.is_empty() {
+                    // `query` continues below the child, so the child is
+                    // split as well
+                    let (_, query_rest) = query.split_at(node.key.borrow().len());
+                    let right = Node {
+                        key: node.key.borrow().to_owned(),
+                        trie: node.trie.split_off(query_rest),
+                    };
+                    let mut nodes: Vec<_> = right.compress().into_iter().collect();
+                    nodes.extend(self.nodes.drain(idx + 1..));
+
+                    let left = self.nodes.pop().unwrap();
+                    self.nodes.extend(left.compress());
+                    return Trie { value: None, nodes };
+                }
+
+                // the keys in the child are either all before or all after
+                // `query`
+                match P::Ref::cmp_first(node_rest, query_rest) {
+                    Ordering::Less => idx + 1,
+                    _ => idx,
+                }
+            }
+        };
+
+        Trie {
+            value: None,
+            nodes: self.nodes.drain(idx..).collect(),
+        }
+    }
+}
+
+impl<P, V> Node<P, V>
+where
+    P: Key,
+{
+    /// Merge `other`, whose key starts with the same unit, into the node.
+    /// Returns `None` if no entry is left below the node.
+    fn merge<F>(self, other: Node<P, V>, merge: &mut Merge<F>) -> Option<Node<P, V>>
+    where
+        F: FnMut(V, V) -> Option<V>,
+    {
+        let (prefix, _, _) = P::Ref::prefix(self.key.borrow(), other.key.borrow());
+        let prefix_len = prefix.len();
+
+        let mut node = self.split(prefix_len);
+        node.trie.merge(other.split(prefix_len).trie, merge);
+        node.compress()
+    }
+
+    /// Split the key of the node after `len` units, into a node with only
+    /// that part of the key, and a lone child with the rest
+    fn split(self, len: usize) -> Node<P, V> {
+        if len == self.key.borrow().len() {
+            return self;
+        }
+
+        let (prefix, rest) = self.key.borrow().split_at(len);
+        let mut trie = Trie::with_value_and_capacity(None, 1);
+        trie.nodes.push(Node {
+            key: rest.to_owned(),
+            trie: self.trie,
+        });
+        Node {
+            key: prefix.to_owned(),
+            trie,
+        }
+    }
+
+    /// Drop the node if it has no value or children, and merge it with its
+    /// child if it has no value and only one child
+    fn compress(mut self) -> Option<Node<P, V>> {
+        match (self.trie.value.is_some(), self.trie.nodes.len()) {
+            (false, 0) => None,
+            (false, 1) => {
+                let child = self.trie.nodes.pop().unwrap();
+                Some(Node {
+                    key: self.key.concat(child.key),
+                    trie: child.trie,
+                })
+            }
+            _ => Some(self),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::BTreeMap;
+
+    use itertools::assert_equal;
+
+    use crate::radix_trie::RadixTrie;
+
+    fn from_keys(keys: &[&str], offset: i32) -> RadixTrie<String, i32> {
+        keys.iter()
+            .enumerate()
+            .map(|(idx, key)| (key.to_string(), idx as i32 + offset))
+            .collect()
+    }
+
+    fn left() -> RadixTrie<String, i32> {
+        from_keys(&["", "ab", "abc", "abde", "b", "car", "cart", "dog"], 0)
+    }
+
+    fn right() -> RadixTrie<String, i32> {
+        from_keys(&["abc", "abd", "abdf", "b", "cat", "do", "dog", "e"], 10)
+    }
+
+    fn entries(trie: &RadixTrie<String, i32>) -> Vec<(String, i32)> {
+        trie.iter().map(|(key, value)| (key, *value)).collect()
+    }
+
+    /// Entries of the `left` and `right` tries, merged by `f` from the keys
+    /// in only `left`, only `right`, or both, with BTreeMaps
+    fn expected<F>(f: F) -> Vec<(String, i32)>
+    where
+        F: Fn(Option<i32>, Option<i32>) -> Option<i32>,
+    {
+        let left: BTreeMap<_, _> = left().into_iter().collect();
+        let right: BTreeMap<_, _> = right().into_iter().collect();
+        let mut keys: Vec<_> = left.keys().chain(right.keys()).cloned().collect();
+        keys.sort();
+        keys.dedup();
+        keys.into_iter()
+            .filter_map(|key| {
+                let value = f(left.get(&key).copied(), right.get(&key).copied())?;
+                Some((key, value))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_union() {
+        let mut trie = left();
+        trie.union_with(right(), |value, other_value| value * 100 + other_value);
+        assert_eq!(
+            expected(|value, other_value| match (value, other_value) {
+                (Some(value), Some(other_value)) => Some(value * 100 + other_value),
+                (value, other_value) => value.or(other_value),
+            }),
+            entries(&trie)
+        );
+        assert_eq!(13, trie.len());
+        assert_eq!(Some(&210), trie.get("abc"));
+        assert_eq!(Some(&11), trie.get("abd"));
+
+        let mut empty = RadixTrie::new();
+        empty.union_with(left(), |_, _| unreachable!());
+        assert_equal(left().iter(), empty.iter());
+        assert_eq!(left().len(), empty.len());
+    }
+
+    #[test]
+    fn test_intersection() {
+        let mut trie = left();
+        trie.intersection_with(right(), |value, other_value| value * 100 + other_value);
+        assert_eq!(
+            vec![("abc".into(), 210), ("b".into(), 413), ("dog".into(), 716)],
+            entries(&trie)
+        );
+        assert_eq!(3, trie.len());
+
+        let mut disjoint = left();
+        disjoint.intersection_with(from_keys(&["x"], 0), |value, _| value);
+        assert!(disjoint.is_empty());
+        assert_eq!(None, disjoint.get(""));
+    }
+
+    #[test]
+    fn test_difference() {
+        let mut trie = left();
+        trie.difference(right());
+        assert_eq!(
+            expected(|value, other_value| value.filter(|_| other_value.is_none())),
+            entries(&trie)
+        );
+        assert_eq!(5, trie.len());
+        assert_eq!(Some(&3), trie.get("abde"));
+        assert!(!trie.contains_key("abc"));
+    }
+
+    #[test]
+    fn test_symmetric_difference() {
+        let mut trie = left();
+        trie.symmetric_difference(right());
+        assert_eq!(
+            expected(|value, other_value| match (value, other_value) {
+                (Some(_), Some(_)) => None,
+                (value, other_value) => value.or(other_value),
+            }),
+            entries(&trie)
+        );
+        assert_eq!(10, trie.len());
+
+        let mut trie = left();
+        trie.symmetric_difference(left());
+        assert!(trie.is_empty());
+        assert_eq!(0, trie.iter().count());
+    }
+
+    #[test]
+    fn test_append() {
+        let mut trie = left();
+        let mut other = right();
+        trie.append(&mut other);
+        assert_eq!(
+            expected(|value, other_value| other_value.or(value)),
+            entries(&trie)
+        );
+        assert_eq!(13, trie.len());
+        assert!(other.is_empty());
+        assert_eq!(0, other.iter().count());
+    }
+
+    #[test]
+    fn test_split_off() {
+        let keys = left().into_keys().collect::<Vec<_>>();
+        for at in ["", "a", "ab", "abc", "abd", "abdf", "b", "ca", "cas", "z"] {
+            let mut trie = left();
+            let mut other = trie.split_off(at);
+
+            let (before, after): (Vec<_>, Vec<_>) =
+                keys.iter().cloned().partition(|key| key.as_str() < at);
+            assert_eq!(before, trie.keys().collect::<Vec<_>>(), "{at:?}");
+            assert_eq!(after, other.keys().collect::<Vec<_>>(), "{at:?}");
+            assert_eq!(before.len(), trie.len());
+            assert_eq!(after.len(), other.len());
+
+            // and back together again
+            trie.append(&mut other);
+            assert_equal(left().iter(), trie.iter());
+        }
+    }
+}
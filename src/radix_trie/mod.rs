@@ -1,16 +1,41 @@
-use std::{borrow::Borrow, mem};
+use std::{borrow::Borrow, cmp::Ordering, mem, ops::RangeBounds};
 
+pub mod automaton;
+pub mod builder;
+pub mod common_prefixes;
 mod debug_impl;
+pub mod entry;
+pub mod fuzzy;
+pub mod glob;
+pub mod into_iter;
+pub mod ip_net;
 pub mod iter;
 mod iter_mut;
 mod key;
+pub mod key_bits_impl;
+pub mod key_path_impl;
 pub mod key_string_impl;
+pub mod key_vec_impl;
+mod merge;
+pub mod range;
+#[cfg(feature = "serde")]
+pub mod serde_impl;
 
 #[cfg(feature = "run_fuzzer_tests")]
 #[cfg(test)]
 mod fuzzer_tests;
 
-use self::{iter::Iter, iter_mut::IterMut};
+use self::{
+    automaton::{Automaton, Search},
+    builder::RadixTrieBuilder,
+    common_prefixes::CommonPrefixes,
+    entry::Entry,
+    fuzzy::Fuzzy,
+    into_iter::{IntoKeys, IntoValues},
+    iter::{Iter, Keys, Values},
+    iter_mut::{IterMut, ValuesMut},
+    range::{Range, RangeMut},
+};
 pub use key::{Key, KeyRef};
 
 /// Implementation of a Radix Trie (also known as a Radix Tree, or
@@ -18,36 +43,55 @@ pub use key::{Key, KeyRef};
 ///
 /// <https://en.wikipedia.org/wiki/Radix_tree>
 pub struct RadixTrie<P, V> {
+    root: Trie<P, V>,
+
+    // Number of values in the trie (see `check_len_invariant`)
+    len: usize,
+}
+
+// Deserialized with a limit on its depth, see `serde_impl::structural`
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+struct Trie<P, V> {
     // Interior nodes may have an optional value. An invariant that must be
     // held is that leaf nodes _must_ contain a value (see
     // `check_leaf_node_some_invariant`).
     value: Option<V>,
 
-    // List of child nodes. The key of a node is computed by concatenating
-    // all the `key`s starting from the root node to this node.
+    // List of child nodes, ordered by the first unit of their key (see
+    // `check_sorted_children_invariant`). The key of a node is computed by
+    // concatenating all the `key`s starting from the root node to this node.
     nodes: Vec<Node<P, V>>,
 }
 
-pub(self) struct Node<P, V> {
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+struct Node<P, V> {
     key: P,
-    trie: RadixTrie<P, V>,
+    trie: Trie<P, V>,
 }
 
 impl<P, V> RadixTrie<P, V> {
     /// Create an empty trie
     pub fn new() -> RadixTrie<P, V> {
-        Self::with_value_and_capacity(None, 0)
+        RadixTrie {
+            root: Trie::new(),
+            len: 0,
+        }
     }
 
-    fn with_value(value: V) -> RadixTrie<P, V> {
-        Self::with_value_and_capacity(Some(value), 0)
+    /// Number of values in the trie
+    pub fn len(&self) -> usize {
+        self.len
     }
 
-    fn with_value_and_capacity(value: Option<V>, n: usize) -> RadixTrie<P, V> {
-        RadixTrie {
-            value,
-            nodes: Vec::with_capacity(n),
-        }
+    /// Does the trie contain no values?
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Remove all values from the trie
+    pub fn clear(&mut self) {
+        self.root = Trie::new();
+        self.len = 0;
     }
 }
 
@@ -57,10 +101,65 @@ impl<P, V> Default for RadixTrie<P, V> {
     }
 }
 
+impl<P, V> FromIterator<(P, V)> for RadixTrie<P, V>
+where
+    P: Key,
+{
+    fn from_iter<T: IntoIterator<Item = (P, V)>>(iter: T) -> Self {
+        let mut trie = RadixTrie::new();
+        trie.extend(iter);
+        trie
+    }
+}
+
+impl<P, V> Extend<(P, V)> for RadixTrie<P, V>
+where
+    P: Key,
+{
+    fn extend<T: IntoIterator<Item = (P, V)>>(&mut self, iter: T) {
+        for (key, value) in iter {
+            self.insert(key, value);
+        }
+    }
+}
+
+impl<P, V> Trie<P, V> {
+    fn new() -> Trie<P, V> {
+        Self::with_value_and_capacity(None, 0)
+    }
+
+    fn with_value(value: V) -> Trie<P, V> {
+        Self::with_value_and_capacity(Some(value), 0)
+    }
+
+    fn with_value_and_capacity(value: Option<V>, n: usize) -> Trie<P, V> {
+        Trie {
+            value,
+            nodes: Vec::with_capacity(n),
+        }
+    }
+}
+
 impl<P, V> RadixTrie<P, V>
 where
     P: Key,
 {
+    /// Build a trie from `(key, value)` pairs in increasing order of key, in a
+    /// single pass (see [RadixTrieBuilder]). Panics if the keys are not
+    /// sorted, or if a key is repeated.
+    pub fn from_sorted_iter<I>(iter: I) -> RadixTrie<P, V>
+    where
+        I: IntoIterator<Item = (P, V)>,
+    {
+        let mut builder = RadixTrieBuilder::new();
+        for (key, value) in iter {
+            if let Err(err) = builder.push(key, value) {
+                panic!("from_sorted_iter: {}", err);
+            }
+        }
+        builder.build()
+    }
+
     /// Get value corresponding to `key` in the trie (or `None` if it does not
     /// exist)
     pub fn get<Q>(&self, key: &Q) -> Option<&V>
@@ -68,19 +167,50 @@ where
         Q: Borrow<P::Ref> + ?Sized,
     {
         let key: &P::Ref = key.borrow();
-        let ret = self.get_impl(key);
-        self.check_invariants(true);
+        let ret = self.root.get_impl(key);
+        self.check_invariants();
         ret
     }
 
+    /// Get a mutable reference to the value corresponding to `key` in the trie
+    /// (or `None` if it does not exist)
+    pub fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        Q: Borrow<P::Ref> + ?Sized,
+    {
+        let key: &P::Ref = key.borrow();
+        self.root.get_mut_impl(key)
+    }
+
+    /// Does the trie contain a value for `key`?
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        Q: Borrow<P::Ref> + ?Sized,
+    {
+        self.get(key).is_some()
+    }
+
     /// Insert `value` into the trie at `key`. Returns the old value, or
     /// `None` if the value was newly inserted.
     pub fn insert(&mut self, key: P, value: V) -> Option<V> {
-        let ret = self.insert_impl(key.borrow(), value);
-        self.check_invariants(true);
+        let ret = self.root.insert_impl(key.borrow(), value);
+        if ret.is_none() {
+            self.len += 1;
+        }
+        self.check_invariants();
         ret
     }
 
+    /// Get the entry for `key` in the trie, for in-place manipulation of its
+    /// value.
+    pub fn entry(&mut self, key: P) -> Entry<'_, P, V> {
+        let RadixTrie { root, len } = self;
+        match root.find_slot(key.borrow()) {
+            Slot::Occupied(value) => Entry::occupied(key, value),
+            Slot::Vacant(slot) => Entry::vacant(key, slot, len),
+        }
+    }
+
     /// Remove the value at `key` from the trie and return it. `None` if the
     /// value did not exist in the trie.
     pub fn remove<Q>(&mut self, key: &Q) -> Option<V>
@@ -88,9 +218,8 @@ where
         Q: Borrow<P::Ref> + ?Sized,
     {
         let key: &P::Ref = key.borrow();
-        let ret = self.remove_impl(0, key);
-        self.check_invariants(true);
-        match ret {
+        let ret = self.root.remove_impl(0, key);
+        let ret = match ret {
             RemoveResult::Skip => None,
             // for the root node, most values of ::Done are ignored, because
             // the root node itself is not dropped even if its value is None
@@ -101,118 +230,622 @@ where
                 has_value: _,
                 removed_value: value,
             } => value,
+        };
+        if ret.is_some() {
+            self.len -= 1;
         }
+        self.check_invariants();
+        ret
     }
 
-    /// Iterater over `(P, &mut V)` pairs that the trie contains.
-    pub fn iter(&self) -> Iter<P, V> {
-        Iter::new(self)
+    /// Remove the value at `key` from the trie, and return it along with the
+    /// key. `None` if the value did not exist in the trie.
+    pub fn remove_entry<Q>(&mut self, key: &Q) -> Option<(P, V)>
+    where
+        Q: Borrow<P::Ref> + ?Sized,
+    {
+        let key: &P::Ref = key.borrow();
+        let value = self.remove(key)?;
+        Some((key.to_owned(), value))
     }
 
-    /// Mutable iterater over `(P, &V)` pairs that the trie contains.
-    pub fn iter_mut(&mut self) -> IterMut<P, V> {
-        IterMut::new(self)
+    /// Iterater over `(P, &V)` pairs that the trie contains, in lexicographic
+    /// order of the keys.
+    pub fn iter(&self) -> Iter<'_, P, V> {
+        Iter::new(&self.root)
     }
 
-    fn get_impl(&self, key: &P::Ref) -> Option<&V> {
-        if key.is_empty() {
-            return self.value.as_ref();
+    /// Mutable iterater over `(P, &mut V)` pairs that the trie contains, in
+    /// lexicographic order of the keys.
+    pub fn iter_mut(&mut self) -> IterMut<'_, P, V> {
+        IterMut::new(&mut self.root)
+    }
+
+    /// Iterator over the keys in the trie, in lexicographic order
+    pub fn keys(&self) -> Keys<'_, P, V> {
+        Keys(self.iter())
+    }
+
+    /// Iterator over the values in the trie, in lexicographic order of their
+    /// keys. Faster than [RadixTrie::iter], as keys are not built.
+    pub fn values(&self) -> Values<'_, P, V> {
+        Values::new(&self.root)
+    }
+
+    /// Mutable iterator over the values in the trie, in lexicographic order
+    /// of their keys. Faster than [RadixTrie::iter_mut], as keys are not
+    /// built.
+    pub fn values_mut(&mut self) -> ValuesMut<'_, P, V> {
+        ValuesMut::new(&mut self.root)
+    }
+
+    /// Owning iterator over the keys in the trie, in lexicographic order
+    pub fn into_keys(self) -> IntoKeys<P, V> {
+        IntoKeys(self.into_iter())
+    }
+
+    /// Owning iterator over the values in the trie, in lexicographic order of
+    /// their keys. Keys are dropped without being built.
+    pub fn into_values(self) -> IntoValues<P, V> {
+        IntoValues::new(self)
+    }
+
+    /// Call `f` with each key and value in the trie, in lexicographic order
+    /// of the keys. Unlike [RadixTrie::iter], keys are borrowed rather than
+    /// allocated for each value.
+    pub fn for_each_with_key<'a, F>(&'a self, f: F)
+    where
+        F: FnMut(&P::Ref, &'a V),
+    {
+        self.iter().for_each_with_key(f)
+    }
+
+    /// Call `f` with each key and mutable value in the trie, in lexicographic
+    /// order of the keys. Unlike [RadixTrie::iter_mut], keys are borrowed
+    /// rather than allocated for each value.
+    pub fn for_each_with_key_mut<'a, F>(&'a mut self, f: F)
+    where
+        F: FnMut(&P::Ref, &'a mut V),
+    {
+        self.iter_mut().for_each_with_key(f)
+    }
+
+    /// Iterate over `(P, &V)` pairs whose key starts with `prefix`, in
+    /// lexicographic order of the keys.
+    pub fn iter_prefix<Q>(&self, prefix: &Q) -> Iter<'_, P, V>
+    where
+        Q: Borrow<P::Ref> + ?Sized,
+    {
+        let mut path = vec![];
+        match self.root.find_prefix(prefix.borrow(), &mut path) {
+            Some(trie) => Iter::with_path(path, trie),
+            None => Iter::empty(),
         }
+    }
 
-        for node in &self.nodes {
-            let (prefix, node_rest, key_rest) = P::Ref::prefix(node.key.borrow(), key);
-            let (prefix_empty, node_rest_empty) = (prefix.is_empty(), node_rest.is_empty());
+    /// Mutable iterator over `(P, &mut V)` pairs whose key starts with
+    /// `prefix`, in lexicographic order of the keys.
+    pub fn iter_prefix_mut<Q>(&mut self, prefix: &Q) -> IterMut<'_, P, V>
+    where
+        Q: Borrow<P::Ref> + ?Sized,
+    {
+        let mut path = vec![];
+        match self.root.find_prefix_mut(prefix.borrow(), &mut path) {
+            Some(trie) => IterMut::with_path(path, trie),
+            None => IterMut::empty(),
+        }
+    }
 
-            // no prefix match, skip this node
-            if prefix_empty {
-                continue;
-            }
+    /// Iterate over `(P, &V)` pairs whose key is within `range`, in
+    /// lexicographic order of the keys. Only the nodes on the way to the
+    /// start and end of the range are searched, so iteration can begin from
+    /// either end. Yields nothing if the start of the range is after its end.
+    pub fn range<Q, R>(&self, range: R) -> Range<'_, P, V>
+    where
+        Q: Borrow<P::Ref> + ?Sized,
+        R: RangeBounds<Q>,
+    {
+        Range::new(Iter::with_range(
+            &self.root,
+            range.start_bound().map(Borrow::borrow),
+            range.end_bound().map(Borrow::borrow),
+        ))
+    }
+
+    /// Mutable iterator over `(P, &mut V)` pairs whose key is within
+    /// `range`, in lexicographic order of the keys
+    pub fn range_mut<Q, R>(&mut self, range: R) -> RangeMut<'_, P, V>
+    where
+        Q: Borrow<P::Ref> + ?Sized,
+        R: RangeBounds<Q>,
+    {
+        RangeMut::new(IterMut::with_range(
+            &mut self.root,
+            range.start_bound().map(Borrow::borrow),
+            range.end_bound().map(Borrow::borrow),
+        ))
+    }
+
+    /// Get the entry with the longest key that is a prefix of `key` (or `None`
+    /// if no key in the trie is a prefix of `key`). The returned key is
+    /// borrowed from `key`.
+    pub fn longest_prefix<'a, Q>(&'a self, key: &'a Q) -> Option<(&'a P::Ref, &'a V)>
+    where
+        Q: Borrow<P::Ref> + ?Sized,
+    {
+        self.common_prefixes(key).last()
+    }
+
+    /// Mutable version of [RadixTrie::longest_prefix]
+    pub fn longest_prefix_mut<'a, Q>(&'a mut self, key: &'a Q) -> Option<(&'a P::Ref, &'a mut V)>
+    where
+        Q: Borrow<P::Ref> + ?Sized,
+    {
+        let key: &P::Ref = key.borrow();
+        let (rest, value) = self.root.longest_prefix_mut_impl(key)?;
+        let (prefix, _) = key.split_at(key.len() - rest.len());
+        Some((prefix, value))
+    }
+
+    /// Iterate over the entries whose key is a prefix of `key`, from the
+    /// shortest key to the longest. The yielded keys are borrowed from `key`.
+    pub fn common_prefixes<'a, Q>(&'a self, key: &'a Q) -> CommonPrefixes<'a, P, V>
+    where
+        Q: Borrow<P::Ref> + ?Sized,
+    {
+        CommonPrefixes::new(&self.root, key.borrow())
+    }
+
+    /// Iterate over the entries whose key `automaton` matches, in
+    /// lexicographic order of the keys. The automaton is stepped along the
+    /// edges of the trie, and the nodes below an edge are skipped as soon as
+    /// it can no longer match.
+    pub fn search<A>(&self, automaton: A) -> Search<'_, P, V, A>
+    where
+        A: Automaton<P::Ref>,
+    {
+        Search::new(&self.root, automaton)
+    }
+
+    /// Iterate over the entries whose key is within Levenshtein distance
+    /// `max_distance` of `query`, along with that distance, in lexicographic
+    /// order of the keys. Distances are counted in units of the key (e.g.
+    /// chars of a `str`). The distances are computed a row at a time while
+    /// descending the trie, so subtrees that are already too far from `query`
+    /// are skipped.
+    pub fn fuzzy<'a, Q>(&'a self, query: &'a Q, max_distance: usize) -> Fuzzy<'a, P, V>
+    where
+        Q: Borrow<P::Ref> + ?Sized,
+    {
+        Fuzzy::new(&self.root, query.borrow(), max_distance)
+    }
+
+    /// Get the entry with the first key in lexicographic order, or `None` if
+    /// the trie is empty
+    pub fn first_key_value(&self) -> Option<(P, &V)> {
+        let mut key = P::Ref::concat(&mut std::iter::empty());
+        let value = self.root.first(&mut key)?;
+        Some((key, value))
+    }
+
+    /// Get the entry with the last key in lexicographic order, or `None` if
+    /// the trie is empty
+    pub fn last_key_value(&self) -> Option<(P, &V)> {
+        let mut key = P::Ref::concat(&mut std::iter::empty());
+        let value = self.root.last(&mut key)?;
+        Some((key, value))
+    }
+
+    /// Remove and return the entry with the first key in lexicographic order
+    pub fn pop_first(&mut self) -> Option<(P, V)> {
+        let mut key = P::Ref::concat(&mut std::iter::empty());
+        let result = self.root.pop_first_impl(0, &mut key);
+        self.pop_result(key, result)
+    }
+
+    /// Remove and return the entry with the last key in lexicographic order
+    pub fn pop_last(&mut self) -> Option<(P, V)> {
+        let mut key = P::Ref::concat(&mut std::iter::empty());
+        let result = self.root.pop_last_impl(0, &mut key);
+        self.pop_result(key, result)
+    }
+
+    fn pop_result(&mut self, key: P, result: RemoveResult<V>) -> Option<(P, V)> {
+        let value = match result {
+            RemoveResult::Skip => None,
+            RemoveResult::Done { removed_value, .. } => removed_value,
+        }?;
+        self.len -= 1;
+        self.check_invariants();
+        Some((key, value))
+    }
+
+    /// Get the entry with the greatest key that is at or before `key` in
+    /// lexicographic order (the floor of `key`), whether or not `key` itself
+    /// is in the trie
+    pub fn lower_bound<Q>(&self, key: &Q) -> Option<(P, &V)>
+    where
+        Q: Borrow<P::Ref> + ?Sized,
+    {
+        let mut found = P::Ref::concat(&mut std::iter::empty());
+        let value = self.root.floor(key.borrow(), &mut found)?;
+        Some((found, value))
+    }
+
+    /// Get the entry with the least key that is at or after `key` in
+    /// lexicographic order (the ceiling of `key`), whether or not `key`
+    /// itself is in the trie
+    pub fn upper_bound<Q>(&self, key: &Q) -> Option<(P, &V)>
+    where
+        Q: Borrow<P::Ref> + ?Sized,
+    {
+        let mut found = P::Ref::concat(&mut std::iter::empty());
+        let value = self.root.ceiling(key.borrow(), &mut found)?;
+        Some((found, value))
+    }
 
-            if node_rest_empty {
-                // consumed the whole child key, delegate getting to the child
-                return node.trie.get_impl(key_rest);
+    /**
+     * Run in debug mode, and under the fuzzer harness
+     * Runtime invariants enforced on the trie structure that should always
+     * hold. Hopefully tests and the fuzzer are enough to ensure that the
+     * implementation is such that they always hold.
+     */
+    fn check_invariants(&self) {
+        #[cfg(debug_assertions)]
+        {
+            let result = self
+                .root
+                .check_structure_invariants()
+                .and_then(|_| self.check_len_invariant());
+            if let Err(invariant) = result {
+                panic!("{}", invariant);
             }
         }
+    }
 
-        None
+    /**
+     * Invariant - the tracked number of values matches the number of values
+     * in the trie
+     */
+    fn check_len_invariant(&self) -> Result<(), &'static str> {
+        if self.root.count_values() != self.len {
+            return Err("len invariant failed");
+        }
+        Ok(())
+    }
+}
+
+impl<P, V> Trie<P, V>
+where
+    P: Key,
+{
+    /// Number of values in the trie, counted by walking all of its nodes
+    fn count_values(&self) -> usize {
+        let child_values: usize = self.nodes.iter().map(|node| node.trie.count_values()).sum();
+        child_values + self.value.is_some() as usize
+    }
+
+    /// Binary search the children for the only one that can share a prefix
+    /// with the non-empty `key`, the child whose key has the same first unit.
+    /// Returns `Err` with the index to insert a new child at if there is
+    /// none.
+    fn child_idx(&self, key: &P::Ref) -> Result<usize, usize> {
+        self.nodes
+            .binary_search_by(|node| P::Ref::cmp_first(node.key.borrow(), key))
     }
 
-    fn insert_impl<'a>(&'a mut self, key: &'a P::Ref, value: V) -> Option<V> {
-        // key is empty, this is the exact node being targeted, insert here
+    /// Find the child node whose key is a prefix of `key`, returning its trie
+    /// and the rest of `key`
+    fn child_prefix_of<'a>(&'a self, key: &'a P::Ref) -> Option<(&'a Trie<P, V>, &'a P::Ref)> {
         if key.is_empty() {
-            return self.value.replace(value);
+            return None;
+        }
+
+        let node = &self.nodes[self.child_idx(key).ok()?];
+        let (_, node_rest, key_rest) = P::Ref::prefix(node.key.borrow(), key);
+        if node_rest.is_empty() {
+            return Some((&node.trie, key_rest));
         }
 
-        for node in &mut self.nodes {
-            let (prefix, node_rest, key_rest) = P::Ref::prefix(node.key.borrow(), key);
-            let (prefix_empty, node_rest_empty, key_rest_empty) =
-                (prefix.is_empty(), node_rest.is_empty(), key_rest.is_empty());
+        None
+    }
 
-            if prefix_empty {
-                // no common prefix, skip this node
-                continue;
+    /// Value of the first key in the trie, appending the key to `key`
+    fn first<'a>(&'a self, key: &mut P) -> Option<&'a V> {
+        let mut trie = self;
+        loop {
+            if let Some(value) = &trie.value {
+                return Some(value);
             }
+            let node = trie.nodes.first()?;
+            key.append_ref(node.key.borrow());
+            trie = &node.trie;
+        }
+    }
+
+    /// Value of the last key in the trie, appending the key to `key`
+    fn last<'a>(&'a self, key: &mut P) -> Option<&'a V> {
+        let mut trie = self;
+        while let Some(node) = trie.nodes.last() {
+            key.append_ref(node.key.borrow());
+            trie = &node.trie;
+        }
+        trie.value.as_ref()
+    }
 
-            match (node_rest_empty, key_rest_empty) {
-                // found exact target node
-                (true, true) => {
-                    return node.trie.insert_impl(key_rest, value);
+    /// Value of the greatest key at or before `query`, appending the key to
+    /// `key`. Descends towards `query`, remembering the nearest entry before
+    /// it to fall back on.
+    fn floor<'a>(&'a self, mut query: &P::Ref, key: &mut P) -> Option<&'a V> {
+        let mut trie = self;
+        let mut before = None;
+
+        loop {
+            if query.is_empty() {
+                if let Some(value) = &trie.value {
+                    return Some(value);
                 }
+                break;
+            }
+            if let Some(value) = &trie.value {
+                before = Some(((*key).borrow().len(), Nearest::Value(value)));
+            }
 
-                // stopped in an interior that does not yet exist, and want to go down another key
-                (false, false) => {
-                    // create the new fork in the road
+            let idx = match trie.child_idx(query) {
+                Err(idx) => idx,
+                Ok(idx) => {
+                    let node = &trie.nodes[idx];
+                    let (_, node_rest, query_rest) = P::Ref::prefix(node.key.borrow(), query);
+                    if node_rest.is_empty() {
+                        if let Some(prev) = idx.checked_sub(1) {
+                            before =
+                                Some(((*key).borrow().len(), Nearest::Last(&trie.nodes[prev])));
+                        }
+                        key.append_ref(node.key.borrow());
+                        (_, query) = query.split_at(node.key.borrow().len());
+                        trie = &node.trie;
+                        continue;
+                    }
+
+                    // the keys in the child are either all before or all
+                    // after `query`
+                    match P::Ref::cmp_first(node_rest, query_rest) {
+                        Ordering::Less => idx + 1,
+                        _ => idx,
+                    }
+                }
+            };
+            if let Some(prev) = idx.checked_sub(1) {
+                before = Some(((*key).borrow().len(), Nearest::Last(&trie.nodes[prev])));
+            }
+            break;
+        }
 
-                    let interior_trie = RadixTrie::with_value_and_capacity(None, 2);
+        let (key_len, nearest) = before?;
+        key.truncate(key_len);
+        match nearest {
+            Nearest::Value(value) => Some(value),
+            Nearest::Last(node) => {
+                key.append_ref(node.key.borrow());
+                node.trie.last(key)
+            }
+        }
+    }
 
-                    let left_fork = Node {
-                        key: node_rest.to_owned(),
-                        trie: mem::replace(&mut node.trie, interior_trie),
-                    };
+    /// Value of the least key at or after `query`, appending the key to
+    /// `key`. Descends towards `query`, remembering the nearest child after
+    /// it to fall back on.
+    fn ceiling<'a>(&'a self, mut query: &P::Ref, key: &mut P) -> Option<&'a V> {
+        let mut trie = self;
+        let mut after = None;
+
+        loop {
+            // every key in `trie` is at or after `query`
+            if query.is_empty() {
+                return trie.first(key);
+            }
 
-                    let right_fork = Node {
-                        key: key_rest.to_owned(),
-                        trie: RadixTrie::with_value(value),
-                    };
+            let idx = match trie.child_idx(query) {
+                Err(idx) => idx,
+                Ok(idx) => {
+                    let node = &trie.nodes[idx];
+                    let (_, node_rest, query_rest) = P::Ref::prefix(node.key.borrow(), query);
+                    if node_rest.is_empty() {
+                        if let Some(next) = trie.nodes.get(idx + 1) {
+                            after = Some(((*key).borrow().len(), next));
+                        }
+                        key.append_ref(node.key.borrow());
+                        (_, query) = query.split_at(node.key.borrow().len());
+                        trie = &node.trie;
+                        continue;
+                    }
+
+                    match P::Ref::cmp_first(node_rest, query_rest) {
+                        Ordering::Less => idx + 1,
+                        _ => idx,
+                    }
+                }
+            };
+            if let Some(next) = trie.nodes.get(idx) {
+                after = Some(((*key).borrow().len(), next));
+            }
+            break;
+        }
 
-                    node.key = prefix.to_owned();
-                    node.trie.nodes.push(left_fork);
-                    node.trie.nodes.push(right_fork);
+        let (key_len, node) = after?;
+        key.truncate(key_len);
+        key.append_ref(node.key.borrow());
+        node.trie.first(key)
+    }
 
-                    return None;
+    /// Returns the value of the longest key that is a prefix of `key`, along
+    /// with the part of `key` after that prefix
+    fn longest_prefix_mut_impl<'a>(
+        &'a mut self,
+        key: &'a P::Ref,
+    ) -> Option<(&'a P::Ref, &'a mut V)> {
+        let child_idx = if key.is_empty() {
+            None
+        } else {
+            self.child_idx(key).ok()
+        };
+        let Trie { value, nodes } = self;
+
+        if let Some(idx) = child_idx {
+            let node = &mut nodes[idx];
+            let (_, node_rest, key_rest) = P::Ref::prefix(node.key.borrow(), key);
+            if node_rest.is_empty() {
+                if let Some(found) = node.trie.longest_prefix_mut_impl(key_rest) {
+                    return Some(found);
                 }
+            }
+        }
 
-                // stopped at an interior node, but not creating a fork
-                (false, true) => {
-                    // create the new interior node
-                    let new_child_node = Node {
-                        key: node_rest.to_owned(),
-                        trie: mem::replace(
-                            &mut node.trie,
-                            RadixTrie::with_value_and_capacity(Some(value), 1),
-                        ),
-                    };
+        value.as_mut().map(|value| (key, value))
+    }
 
-                    node.key = prefix.to_owned();
-                    node.trie.nodes.push(new_child_node);
+    /// Find the deepest trie which holds every key starting with `prefix`.
+    /// The keys of the nodes leading to that trie are pushed onto `path`.
+    /// The last of those keys may extend past the end of `prefix`, if
+    /// `prefix` ends partway through a node's key.
+    fn find_prefix<'a>(
+        &'a self,
+        prefix: &P::Ref,
+        path: &mut Vec<&'a P::Ref>,
+    ) -> Option<&'a Trie<P, V>> {
+        if prefix.is_empty() {
+            return Some(self);
+        }
 
-                    return None;
-                }
-                // no more of this child, but there is more key -
-                // recurse
-                (true, false) => {
-                    return node.trie.insert_impl(key_rest, value);
-                }
+        let node = &self.nodes[self.child_idx(prefix).ok()?];
+        let (_, node_rest, prefix_rest) = P::Ref::prefix(node.key.borrow(), prefix);
+
+        if prefix_rest.is_empty() {
+            // prefix ends within (or at the end of) this node's key
+            path.push(node.key.borrow());
+            return Some(&node.trie);
+        }
+
+        if node_rest.is_empty() {
+            // consumed the whole child key, continue with the child
+            path.push(node.key.borrow());
+            return node.trie.find_prefix(prefix_rest, path);
+        }
+
+        // the prefix diverges from this node's key, nothing can match
+        None
+    }
+
+    /// Mutable version of `find_prefix`
+    fn find_prefix_mut<'a>(
+        &'a mut self,
+        prefix: &P::Ref,
+        path: &mut Vec<&'a P::Ref>,
+    ) -> Option<&'a mut Trie<P, V>> {
+        if prefix.is_empty() {
+            return Some(self);
+        }
+
+        let idx = self.child_idx(prefix).ok()?;
+        let Node { key, trie } = &mut self.nodes[idx];
+        let key: &'a P = key;
+        let key: &'a P::Ref = key.borrow();
+        let (_, node_rest, prefix_rest) = P::Ref::prefix(key, prefix);
+
+        if prefix_rest.is_empty() {
+            path.push(key);
+            return Some(trie);
+        }
+
+        if node_rest.is_empty() {
+            path.push(key);
+            return trie.find_prefix_mut(prefix_rest, path);
+        }
+
+        None
+    }
+
+    fn get_mut_impl(&mut self, key: &P::Ref) -> Option<&mut V> {
+        if key.is_empty() {
+            return self.value.as_mut();
+        }
+
+        let idx = self.child_idx(key).ok()?;
+        let node = &mut self.nodes[idx];
+        let (_, node_rest, key_rest) = P::Ref::prefix(node.key.borrow(), key);
+
+        if node_rest.is_empty() {
+            // consumed the whole child key, delegate getting to the child
+            return node.trie.get_mut_impl(key_rest);
+        }
+
+        None
+    }
+
+    fn get_impl(&self, key: &P::Ref) -> Option<&V> {
+        if key.is_empty() {
+            return self.value.as_ref();
+        }
+
+        let node = &self.nodes[self.child_idx(key).ok()?];
+        let (_, node_rest, key_rest) = P::Ref::prefix(node.key.borrow(), key);
+
+        if node_rest.is_empty() {
+            // consumed the whole child key, delegate getting to the child
+            return node.trie.get_impl(key_rest);
+        }
+
+        None
+    }
+
+    fn insert_impl(&mut self, key: &P::Ref, value: V) -> Option<V> {
+        match self.find_slot(key) {
+            Slot::Occupied(old_value) => Some(mem::replace(old_value, value)),
+            Slot::Vacant(slot) => {
+                slot.insert(key, value);
+                None
             }
         }
+    }
+
+    /// Find where the value for `key` lives in the trie, or where it would
+    /// be inserted if there is no value for `key` yet
+    fn find_slot(&mut self, key: &P::Ref) -> Slot<'_, P, V> {
+        // key is empty, this is the exact node being targeted
+        if key.is_empty() {
+            return match self.value {
+                Some(ref mut value) => Slot::Occupied(value),
+                None => Slot::Vacant(VacantSlot::Value(&mut self.value)),
+            };
+        }
 
-        // no relevant existing child node found, insert as a new subnode
-        self.nodes.push(Node {
-            key: key.to_owned(),
-            trie: RadixTrie::with_value(value),
+        let found = self.child_idx(key).map(|idx| {
+            let (prefix, node_rest, _) = P::Ref::prefix(self.nodes[idx].key.borrow(), key);
+            (idx, prefix.len(), node_rest.is_empty())
         });
 
-        None
+        match found {
+            // consumed the whole child key, recurse
+            Ok((idx, prefix_len, true)) => {
+                let (_, key_rest) = key.split_at(prefix_len);
+                self.nodes[idx].trie.find_slot(key_rest)
+            }
+
+            // stopped partway through the child key, it must be split
+            Ok((idx, prefix_len, false)) => Slot::Vacant(VacantSlot::Split {
+                node: &mut self.nodes[idx],
+                prefix_len,
+                key_len: key.len(),
+            }),
+
+            // no relevant existing child node found, insert as a new subnode,
+            // keeping the children ordered
+            Err(idx) => Slot::Vacant(VacantSlot::Child {
+                nodes: &mut self.nodes,
+                idx,
+                key_len: key.len(),
+            }),
+        }
     }
 
     fn remove_impl(&mut self, this_idx: usize, key: &P::Ref) -> RemoveResult<V> {
@@ -232,25 +865,67 @@ where
         // by default, no relevant child node was found, so skip any action
         let mut result = RemoveResult::Skip;
 
-        for (idx, node) in self.nodes.iter_mut().enumerate() {
-            let (prefix, node_rest, key_rest) = P::Ref::prefix(node.key.borrow(), key);
-            let (prefix_empty, node_rest_empty) = (prefix.is_empty(), node_rest.is_empty());
-
-            if prefix_empty {
-                // no common prefix, skip this node
-                continue;
-            }
+        if let Ok(idx) = self.child_idx(key) {
+            let node = &mut self.nodes[idx];
+            let (_, node_rest, key_rest) = P::Ref::prefix(node.key.borrow(), key);
 
-            if node_rest_empty {
+            if node_rest.is_empty() {
                 // node's key was entirely consumed, so go down this node key
                 result = node.trie.remove_impl(idx, key_rest);
-                match result {
-                    RemoveResult::Skip => continue,
-                    _ => break,
-                };
             }
         }
 
+        self.repair_after_remove(this_idx, result)
+    }
+
+    /// Remove the value of the first key, appending the key to `key`. The
+    /// same as `remove_impl`, but always descending into the first child.
+    fn pop_first_impl(&mut self, this_idx: usize, key: &mut P) -> RemoveResult<V> {
+        // the value of a trie comes before any of its children's (a trie
+        // without either is the empty root)
+        if self.value.is_some() || self.nodes.is_empty() {
+            return RemoveResult::Done {
+                idx: this_idx,
+                num_children: self.nodes.len(),
+                has_value: false,
+                removed_value: self.value.take(),
+            };
+        }
+
+        let node = &mut self.nodes[0];
+        key.append_ref(node.key.borrow());
+        let result = node.trie.pop_first_impl(0, key);
+        self.repair_after_remove(this_idx, result)
+    }
+
+    /// Remove the value of the last key, appending the key to `key`. The
+    /// same as `remove_impl`, but always descending into the last child.
+    fn pop_last_impl(&mut self, this_idx: usize, key: &mut P) -> RemoveResult<V> {
+        // the last key is always at a leaf (or this is the empty root)
+        let Some(last_idx) = self.nodes.len().checked_sub(1) else {
+            return RemoveResult::Done {
+                idx: this_idx,
+                num_children: 0,
+                has_value: false,
+                removed_value: self.value.take(),
+            };
+        };
+
+        let node = &mut self.nodes[last_idx];
+        key.append_ref(node.key.borrow());
+        let result = node.trie.pop_last_impl(last_idx, key);
+        self.repair_after_remove(this_idx, result)
+    }
+
+    /// After a value was removed from below the child at `idx` (given by a
+    /// `RemoveResult::Done`), remove that child if it is now an empty leaf,
+    /// or merge it with its lone child, and report the state of this trie to
+    /// its parent
+    fn repair_after_remove(
+        &mut self,
+        this_idx: usize,
+        mut result: RemoveResult<V>,
+    ) -> RemoveResult<V> {
         if let RemoveResult::Done {
             idx,
             num_children,
@@ -261,15 +936,16 @@ where
             match (has_value, num_children) {
                 (false, 0) => {
                     // lone empty leaf node, remove it
-                    self.nodes.swap_remove(idx);
+                    self.nodes.remove(idx);
                 }
                 (false, 1) => {
                     // remove the interior node and extend its lone child's
-                    // key
-                    let mut node = self.nodes.swap_remove(idx);
+                    // key. the merged key starts with the interior node's
+                    // key, so it takes the same position among its siblings
+                    let mut node = self.nodes.remove(idx);
                     let mut child = node.trie.nodes.pop().unwrap();
                     child.key = node.key.concat(child.key);
-                    self.nodes.push(child);
+                    self.nodes.insert(idx, child);
                 }
                 _ => {
                     // node has a value, or the node has more than one child
@@ -292,58 +968,226 @@ where
     }
 
     /**
-     * Run in debug mode, and under the fuzzer harness
-     * Runtime invariants enforced on the trie structure that should always
-     * hold. Hopefully tests and the fuzzer are enough to ensure that the
-     * implementation is such that they always hold.
+     * Check the invariants on the structure of a root trie, returning the
+     * first one that does not hold. Also used to validate deserialized tries.
      */
-    fn check_invariants(&self, is_root: bool) {
-        #[cfg(debug_assertions)]
-        {
-            self.check_key_prefix_invariant();
-            self.check_leaf_node_some_invariant(is_root);
-        }
+    fn check_structure_invariants(&self) -> Result<(), &'static str> {
+        self.check_non_empty_key_invariant()?;
+        self.check_sorted_children_invariant()?;
+        self.check_key_prefix_invariant()?;
+        self.check_leaf_node_some_invariant(true)?;
+        self.check_compressed_invariant(true)
     }
 
     /**
      * Invariant - ensure that the prefix invariant holds. No two nodes of
      * a single trie should share a common prefix - if they did, that indicates
      * we did not create an interior node of that common prefix.
+     *
+     * Two keys share a prefix exactly when they share a first unit, and the
+     * children are already ordered by their first unit (see
+     * `check_sorted_children_invariant`, which must hold first), so only
+     * neighbouring children need to be compared. This keeps the check linear
+     * in the number of children, as it also runs on untrusted input.
      */
-    fn check_key_prefix_invariant(&self) {
-        for (idx1, n1) in self.nodes.iter().enumerate() {
-            for (idx2, n2) in self.nodes.iter().enumerate() {
-                if idx1 == idx2 {
-                    continue;
-                }
+    fn check_key_prefix_invariant(&self) -> Result<(), &'static str> {
+        for pair in self.nodes.windows(2) {
+            if P::Ref::cmp_first(pair[0].key.borrow(), pair[1].key.borrow()) != Ordering::Less {
+                return Err("no shared prefixes invariant failed");
+            }
+        }
 
-                let (prefix, _, _) = P::Ref::prefix(n1.key.borrow(), n2.key.borrow());
-                if !P::Ref::is_empty(prefix) {
-                    panic!("no shared prefixes invariant failed");
-                }
+        for node in &self.nodes {
+            node.trie.check_key_prefix_invariant()?;
+        }
+        Ok(())
+    }
+
+    /**
+     * Invariant - the key of a node is never empty, as the value for that key
+     * would belong in the parent trie.
+     */
+    fn check_non_empty_key_invariant(&self) -> Result<(), &'static str> {
+        for node in &self.nodes {
+            if node.key.borrow().is_empty() {
+                return Err("non-empty key invariant failed");
+            }
+            node.trie.check_non_empty_key_invariant()?;
+        }
+        Ok(())
+    }
+
+    /**
+     * Invariant - child nodes are ordered by the first unit of their key, so
+     * that iteration yields keys in lexicographic order. Children with the
+     * same first unit are caught by `check_key_prefix_invariant`.
+     */
+    fn check_sorted_children_invariant(&self) -> Result<(), &'static str> {
+        for pair in self.nodes.windows(2) {
+            if P::Ref::cmp_first(pair[0].key.borrow(), pair[1].key.borrow()) == Ordering::Greater {
+                return Err("sorted children invariant failed");
             }
         }
 
         for node in &self.nodes {
-            node.trie.check_key_prefix_invariant();
+            node.trie.check_sorted_children_invariant()?;
         }
+        Ok(())
     }
 
     /**
      * Invariant - leaf nodes should always have a Some(_) value
      */
-    fn check_leaf_node_some_invariant(&self, is_root: bool) {
+    fn check_leaf_node_some_invariant(&self, is_root: bool) -> Result<(), &'static str> {
         // (not relevant to root node)
         // a node that has no child nodes must be a Some(_) value
         if !is_root && self.nodes.is_empty() && self.value.is_none() {
-            panic!("leaf node Some(_) invariant failed");
+            return Err("leaf node Some(_) invariant failed");
         }
         for node in &self.nodes {
-            node.trie.check_leaf_node_some_invariant(false);
+            node.trie.check_leaf_node_some_invariant(false)?;
+        }
+        Ok(())
+    }
+
+    /**
+     * Invariant - interior nodes without a value fork into at least two
+     * children, otherwise the node should have been merged with its child
+     */
+    fn check_compressed_invariant(&self, is_root: bool) -> Result<(), &'static str> {
+        // (not relevant to root node)
+        if !is_root && self.value.is_none() && self.nodes.len() == 1 {
+            return Err("compressed node invariant failed");
+        }
+        for node in &self.nodes {
+            node.trie.check_compressed_invariant(false)?;
+        }
+        Ok(())
+    }
+}
+
+/// Result of `Trie::find_slot`
+enum Slot<'a, P, V> {
+    Occupied(&'a mut V),
+    Vacant(VacantSlot<'a, P, V>),
+}
+
+/// Where a key without a value would be inserted into the trie
+enum VacantSlot<'a, P, V> {
+    /// the trie for the key exists, but has no value
+    Value(&'a mut Option<V>),
+
+    /// the key shares the first `prefix_len` units with `node`'s key, and
+    /// then either diverges from it or ends. `key_len` is the length of the
+    /// part of the key at `node`.
+    Split {
+        node: &'a mut Node<P, V>,
+        prefix_len: usize,
+        key_len: usize,
+    },
+
+    /// no child shares a prefix with the key, so a new child is inserted at
+    /// `idx`. `key_len` is the length of the part of the key at `nodes`.
+    Child {
+        nodes: &'a mut Vec<Node<P, V>>,
+        idx: usize,
+        key_len: usize,
+    },
+}
+
+impl<'a, P, V> VacantSlot<'a, P, V>
+where
+    P: Key,
+{
+    /// Insert `value` into the slot, creating or splitting nodes as needed.
+    /// `key` is the full key that the slot was found for.
+    fn insert(self, key: &P::Ref, value: V) -> &'a mut V {
+        match self {
+            VacantSlot::Value(slot) => slot.insert(value),
+
+            VacantSlot::Split {
+                node,
+                prefix_len,
+                key_len,
+            } => {
+                let (_, key) = key.split_at(key.len() - key_len);
+                let (prefix, node_rest) = node.key.borrow().split_at(prefix_len);
+                let (_, key_rest) = key.split_at(prefix_len);
+                let (prefix, node_rest) = (prefix.to_owned(), node_rest.to_owned());
+
+                if key_rest.is_empty() {
+                    // stopped at an interior node, but not creating a fork.
+                    // create the new interior node
+                    let new_child_node = Node {
+                        key: node_rest,
+                        trie: mem::replace(
+                            &mut node.trie,
+                            Trie::with_value_and_capacity(Some(value), 1),
+                        ),
+                    };
+
+                    node.key = prefix;
+                    node.trie.nodes.push(new_child_node);
+
+                    return node.trie.value.as_mut().unwrap();
+                }
+
+                // stopped in an interior that does not yet exist, and want to
+                // go down another key. create the new fork in the road,
+                // keeping the forks ordered
+                let left_first = P::Ref::cmp_first(node_rest.borrow(), key_rest) == Ordering::Less;
+                let interior_trie = Trie::with_value_and_capacity(None, 2);
+
+                let left_fork = Node {
+                    key: node_rest,
+                    trie: mem::replace(&mut node.trie, interior_trie),
+                };
+
+                let right_fork = Node {
+                    key: key_rest.to_owned(),
+                    trie: Trie::with_value(value),
+                };
+
+                node.key = prefix;
+                let right_idx = if left_first {
+                    node.trie.nodes.push(left_fork);
+                    node.trie.nodes.push(right_fork);
+                    1
+                } else {
+                    node.trie.nodes.push(right_fork);
+                    node.trie.nodes.push(left_fork);
+                    0
+                };
+
+                node.trie.nodes[right_idx].trie.value.as_mut().unwrap()
+            }
+
+            VacantSlot::Child {
+                nodes,
+                idx,
+                key_len,
+            } => {
+                let (_, key) = key.split_at(key.len() - key_len);
+                nodes.insert(
+                    idx,
+                    Node {
+                        key: key.to_owned(),
+                        trie: Trie::with_value(value),
+                    },
+                );
+
+                nodes[idx].trie.value.as_mut().unwrap()
+            }
         }
     }
 }
 
+/// Nearest entry to a query, found by `Trie::floor`
+enum Nearest<'a, P, V> {
+    Value(&'a V),
+    Last(&'a Node<P, V>),
+}
+
 enum RemoveResult<V> {
     Skip,
     Done {
@@ -356,27 +1200,30 @@ enum RemoveResult<V> {
 
 #[cfg(test)]
 mod test {
-    use super::{Node, RadixTrie};
+    use super::{Node, RadixTrie, Trie};
 
     fn get_test_trie() -> RadixTrie<String, i32> {
         RadixTrie {
-            value: None,
-            nodes: vec![Node {
-                key: "do".into(),
-                trie: RadixTrie {
-                    value: None,
-                    nodes: vec![
-                        Node {
-                            key: "g".into(),
-                            trie: RadixTrie::with_value(1),
-                        },
-                        Node {
-                            key: "ts".into(),
-                            trie: RadixTrie::with_value(2),
-                        },
-                    ],
-                },
-            }],
+            root: Trie {
+                value: None,
+                nodes: vec![Node {
+                    key: "do".into(),
+                    trie: Trie {
+                        value: None,
+                        nodes: vec![
+                            Node {
+                                key: "g".into(),
+                                trie: Trie::with_value(1),
+                            },
+                            Node {
+                                key: "ts".into(),
+                                trie: Trie::with_value(2),
+                            },
+                        ],
+                    },
+                }],
+            },
+            len: 2,
         }
     }
 
@@ -411,6 +1258,167 @@ mod test {
         assert_eq!(Some(&11), trie.get("dotty"));
     }
 
+    #[test]
+    fn test_get_mut() {
+        let mut trie = get_test_trie();
+        assert_eq!(None, trie.get_mut(""));
+        assert_eq!(None, trie.get_mut("do"));
+        assert_eq!(None, trie.get_mut("dolt"));
+
+        *trie.get_mut("dog").unwrap() += 10;
+        assert_eq!(Some(&11), trie.get("dog"));
+        assert_eq!(Some(&mut 2), trie.get_mut(&"dots".to_owned()));
+    }
+
+    #[test]
+    fn test_contains_key() {
+        let trie = get_test_trie();
+        assert!(!trie.contains_key(""));
+        assert!(!trie.contains_key("do"));
+        assert!(trie.contains_key("dog"));
+        assert!(trie.contains_key("dots"));
+        assert!(!trie.contains_key("dotsy"));
+    }
+
+    #[test]
+    fn test_remove_entry() {
+        let mut trie = get_test_trie();
+        assert_eq!(None, trie.remove_entry("do"));
+        assert_eq!(Some(("dog".to_owned(), 1)), trie.remove_entry("dog"));
+        assert_eq!(None, trie.remove_entry("dog"));
+        assert_eq!(Some(&2), trie.get("dots"));
+    }
+
+    #[test]
+    fn test_len_and_clear() {
+        let mut trie = get_test_trie();
+        assert_eq!(2, trie.len());
+        assert!(!trie.is_empty());
+
+        trie.insert("dog".into(), 3);
+        assert_eq!(2, trie.len());
+        trie.insert("d".into(), 4);
+        assert_eq!(3, trie.len());
+        *trie.entry("cat".into()).or_insert(0) += 1;
+        assert_eq!(4, trie.len());
+        trie.remove("do");
+        assert_eq!(4, trie.len());
+        trie.remove("dots");
+        assert_eq!(3, trie.len());
+
+        trie.clear();
+        assert_eq!(0, trie.len());
+        assert!(trie.is_empty());
+        assert_eq!(None, trie.get("dog"));
+        assert_eq!(0, trie.iter().count());
+
+        trie.insert("dog".into(), 5);
+        assert_eq!(1, trie.len());
+    }
+
+    #[test]
+    fn test_longest_prefix() {
+        let mut trie = get_test_trie();
+        assert_eq!(None, trie.longest_prefix(""));
+        assert_eq!(None, trie.longest_prefix("do"));
+        assert_eq!(Some(("dog", &1)), trie.longest_prefix("dog"));
+        assert_eq!(Some(("dog", &1)), trie.longest_prefix("doggy"));
+        assert_eq!(None, trie.longest_prefix("dot"));
+
+        trie.insert("d".into(), 3);
+        assert_eq!(Some(("d", &3)), trie.longest_prefix("dot"));
+        assert_eq!(Some(("dots", &2)), trie.longest_prefix("dots/1"));
+
+        trie.insert("".into(), 4);
+        assert_eq!(Some(("", &4)), trie.longest_prefix("cat"));
+    }
+
+    #[test]
+    fn test_longest_prefix_mut() {
+        let mut trie = get_test_trie();
+        assert_eq!(None, trie.longest_prefix_mut("do"));
+
+        if let Some((key, value)) = trie.longest_prefix_mut("dogs") {
+            assert_eq!("dog", key);
+            *value += 10;
+        }
+        assert_eq!(Some(&11), trie.get("dog"));
+
+        trie.insert("".into(), 4);
+        assert_eq!(Some(("", &mut 4)), trie.longest_prefix_mut("dot"));
+    }
+
+    #[test]
+    fn test_first_and_last() {
+        let mut trie = get_test_trie();
+        assert_eq!(Some(("dog".into(), &1)), trie.first_key_value());
+        assert_eq!(Some(("dots".into(), &2)), trie.last_key_value());
+
+        trie.insert("".into(), 0);
+        trie.insert("dotsy".into(), 3);
+        assert_eq!(Some(("".into(), &0)), trie.first_key_value());
+        assert_eq!(Some(("dotsy".into(), &3)), trie.last_key_value());
+
+        assert_eq!(Some(("dotsy".into(), 3)), trie.pop_last());
+        assert_eq!(Some(("".into(), 0)), trie.pop_first());
+        assert_eq!(Some(("dog".into(), 1)), trie.pop_first());
+        assert_eq!(Some(("dots".into(), 2)), trie.pop_first());
+        assert_eq!(None, trie.pop_first());
+        assert_eq!(None, trie.pop_last());
+        assert_eq!(None, trie.first_key_value());
+        assert!(trie.is_empty());
+    }
+
+    #[test]
+    fn test_pop_compresses() {
+        let mut trie: RadixTrie<String, i32> = RadixTrie::new();
+        for (value, key) in ["te", "tea", "ten", "to", "tone"].into_iter().enumerate() {
+            trie.insert(key.into(), value as i32);
+        }
+
+        // "te" is left without a value, above "tea" and "ten"
+        assert_eq!(Some(("te".into(), 0)), trie.pop_first());
+        assert_eq!(Some(("tone".into(), 4)), trie.pop_last());
+        // "to" is left as a leaf
+        assert_eq!("t", trie.root.nodes[0].key);
+        assert_eq!(2, trie.root.nodes[0].trie.nodes.len());
+
+        // "te" has only "tea" below it, and is merged into it
+        assert_eq!(Some(("to".into(), 3)), trie.pop_last());
+        assert_eq!(Some(("ten".into(), 2)), trie.pop_last());
+        assert_eq!("tea", trie.root.nodes[0].key);
+        assert_eq!(0, trie.root.nodes[0].trie.nodes.len());
+
+        assert_eq!(Some(("tea".into(), 1)), trie.pop_first());
+        assert!(trie.root.nodes.is_empty());
+        assert_eq!(0, trie.len());
+    }
+
+    #[test]
+    fn test_lower_and_upper_bound() {
+        let mut trie = get_test_trie();
+        trie.insert("do".into(), 0);
+        trie.insert("dotty".into(), 3);
+
+        assert_eq!(None, trie.lower_bound(""));
+        assert_eq!(None, trie.lower_bound("d"));
+        assert_eq!(Some(("do".into(), &0)), trie.lower_bound("do"));
+        assert_eq!(Some(("do".into(), &0)), trie.lower_bound("dof"));
+        assert_eq!(Some(("dog".into(), &1)), trie.lower_bound("dogs"));
+        assert_eq!(Some(("dog".into(), &1)), trie.lower_bound("dot"));
+        assert_eq!(Some(("dots".into(), &2)), trie.lower_bound("dotsy"));
+        assert_eq!(Some(("dotty".into(), &3)), trie.lower_bound("e"));
+
+        assert_eq!(Some(("do".into(), &0)), trie.upper_bound(""));
+        assert_eq!(Some(("do".into(), &0)), trie.upper_bound("do"));
+        assert_eq!(Some(("dog".into(), &1)), trie.upper_bound("doa"));
+        assert_eq!(Some(("dots".into(), &2)), trie.upper_bound("dogs"));
+        assert_eq!(Some(("dots".into(), &2)), trie.upper_bound("dot"));
+        assert_eq!(Some(("dotty".into(), &3)), trie.upper_bound("dotsy"));
+        assert_eq!(None, trie.upper_bound("dotz"));
+        assert_eq!(None, trie.upper_bound("e"));
+    }
+
     #[test]
     fn test_fuzzer_1() {
         let mut trie: RadixTrie<String, ()> = RadixTrie::new();
@@ -0,0 +1,168 @@
+use crate::radix_trie::{iter::Iter, iter_mut::IterMut, Key};
+
+/// Iterator over the entries of a [RadixTrie](super::RadixTrie) whose keys
+/// are within a range, in lexicographic order of the keys. Created by
+/// [RadixTrie::range](super::RadixTrie::range).
+///
+/// Yielded items are a tuple of (P, &V), as for [Iter].
+pub struct Range<'a, P, V>(Iter<'a, P, V>)
+where
+    P: Key;
+
+/// Mutable iterator over the entries of a [RadixTrie](super::RadixTrie)
+/// whose keys are within a range. Created by
+/// [RadixTrie::range_mut](super::RadixTrie::range_mut).
+pub struct RangeMut<'a, P, V>(IterMut<'a, P, V>)
+where
+    P: Key;
+
+impl<'a, P, V> Range<'a, P, V>
+where
+    P: Key,
+{
+    /// `iter` is already limited to the range at both of its ends
+    pub(super) fn new(iter: Iter<'a, P, V>) -> Self {
+        Range(iter)
+    }
+}
+
+impl<'a, P, V> RangeMut<'a, P, V>
+where
+    P: Key,
+{
+    pub(super) fn new(iter: IterMut<'a, P, V>) -> Self {
+        RangeMut(iter)
+    }
+}
+
+impl<'a, P, V> Iterator for Range<'a, P, V>
+where
+    P: Key,
+{
+    type Item = (P, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next()
+    }
+}
+
+impl<'a, P, V> DoubleEndedIterator for Range<'a, P, V>
+where
+    P: Key,
+{
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.0.next_back()
+    }
+}
+
+impl<'a, P, V> Iterator for RangeMut<'a, P, V>
+where
+    P: Key,
+{
+    type Item = (P, &'a mut V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next()
+    }
+}
+
+impl<'a, P, V> DoubleEndedIterator for RangeMut<'a, P, V>
+where
+    P: Key,
+{
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.0.next_back()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{collections::BTreeMap, ops::Bound};
+
+    use itertools::assert_equal;
+
+    use crate::radix_trie::RadixTrie;
+
+    fn keys<'a>(iter: impl Iterator<Item = (String, &'a usize)>) -> Vec<String> {
+        iter.map(|(key, _)| key).collect()
+    }
+
+    fn get_test_trie() -> RadixTrie<String, usize> {
+        [
+            "", "a", "ab", "abc", "abd", "b", "ba", "dog", "dots", "dotty", "Ю",
+        ]
+        .into_iter()
+        .enumerate()
+        .map(|(idx, key)| (key.to_owned(), idx))
+        .collect()
+    }
+
+    #[test]
+    fn test_works() {
+        let trie = get_test_trie();
+
+        assert_equal(trie.iter(), trie.range::<str, _>(..));
+        assert_eq!(vec!["ab", "abc", "abd"], keys(trie.range("ab".."b")));
+        assert_eq!(vec!["ab", "abc", "abd", "b"], keys(trie.range("ab"..="b")));
+        assert_eq!(vec!["abd", "b", "ba"], keys(trie.range("abcd".."bb")));
+        assert_eq!(vec!["", "a"], keys(trie.range(.."aa")));
+        assert_eq!(vec!["dotty", "Ю"], keys(trie.range("dott"..)));
+        assert_eq!(vec!["dog", "dots", "dotty"], keys(trie.range("do".."du")));
+        assert_eq!(
+            vec!["abc", "abd"],
+            keys(trie.range::<str, _>((Bound::Excluded("ab"), Bound::Excluded("b"))))
+        );
+        assert_eq!(
+            vec!["dotty", "dots", "dog"],
+            keys(trie.range("do".."du").rev())
+        );
+        assert_eq!(vec!["b", "abd"], keys(trie.range("abcd"..="b").rev()));
+        assert_eq!(0, trie.range("c".."d").count());
+        assert_eq!(0, trie.range("c".."d").rev().count());
+        assert_eq!(0, trie.range("b".."a").count());
+        assert_eq!(0, trie.range("b".."a").rev().count());
+    }
+
+    #[test]
+    fn test_matches_btree_map() {
+        let trie = get_test_trie();
+        let truth: BTreeMap<_, _> = trie.iter().collect();
+        let bounds = [
+            "", "a", "aa", "abc", "abcd", "b", "c", "dot", "dotz", "Ю", "Я",
+        ];
+
+        for start in bounds {
+            for end in bounds.into_iter().filter(|&end| start <= end) {
+                let expected = truth
+                    .range::<str, _>((Bound::Included(start), Bound::Excluded(end)))
+                    .map(|(k, v)| (k.clone(), *v));
+                assert_equal(expected.clone().rev(), trie.range(start..end).rev());
+                assert_equal(expected, trie.range(start..end));
+                let expected = truth
+                    .range::<str, _>((Bound::Included(start), Bound::Included(end)))
+                    .map(|(k, v)| (k.clone(), *v));
+                assert_equal(expected.clone().rev(), trie.range(start..=end).rev());
+                assert_equal(expected, trie.range(start..=end));
+            }
+        }
+    }
+
+    #[test]
+    fn test_range_mut() {
+        let mut trie = get_test_trie();
+        for (_, value) in trie.range_mut("abc".."dots") {
+            *value = 100;
+        }
+        assert_equal(
+            vec![
+                ("abc".into(), &100),
+                ("abd".into(), &100),
+                ("b".into(), &100),
+                ("ba".into(), &100),
+                ("dog".into(), &100),
+                ("dots".into(), &8),
+            ],
+            trie.range("abc"..="dots"),
+        );
+    }
+}
@@ -0,0 +1,542 @@
+use std::{fmt, marker::PhantomData};
+
+use serde::{
+    de::{MapAccess, Visitor},
+    Deserialize, Deserializer, Serialize, Serializer,
+};
+
+use super::{Key, RadixTrie};
+
+/// Serialized as a flat map of key to value, the same as a `HashMap`. See
+/// [structural] for a form that keeps the structure of the trie.
+impl<P, V> Serialize for RadixTrie<P, V>
+where
+    P: Key + Serialize,
+    V: Serialize,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.collect_map(self.iter())
+    }
+}
+
+/// Deserialized from a flat map of key to value, the same as a `HashMap`
+impl<'de, P, V> Deserialize<'de> for RadixTrie<P, V>
+where
+    P: Key + Deserialize<'de>,
+    V: Deserialize<'de>,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_map(MapVisitor(PhantomData))
+    }
+}
+
+struct MapVisitor<P, V>(PhantomData<RadixTrie<P, V>>);
+
+impl<'de, P, V> Visitor<'de> for MapVisitor<P, V>
+where
+    P: Key + Deserialize<'de>,
+    V: Deserialize<'de>,
+{
+    type Value = RadixTrie<P, V>;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a map")
+    }
+
+    fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        let mut trie = RadixTrie::new();
+        while let Some((key, value)) = map.next_entry()? {
+            trie.insert(key, value);
+        }
+        Ok(trie)
+    }
+}
+
+/// Structural form of a [RadixTrie], which keeps its compressed nodes, so
+/// that loading it does not have to split nodes again. Use with
+/// `#[serde(with = "rusty_trees::radix_trie::serde_impl::structural")]`.
+///
+/// Each trie is serialized as `{ "value": Option<V>, "nodes": [{ "key": P,
+/// "trie": ... }] }`. Deserializing checks the invariants of the trie
+/// structure, and rejects input that does not uphold them, or that nests
+/// tries deeper than [structural::MAX_DEPTH].
+pub mod structural {
+    use std::{fmt, marker::PhantomData};
+
+    use serde::{
+        de::{DeserializeSeed, Error, IgnoredAny, MapAccess, SeqAccess, Visitor},
+        Deserialize, Deserializer, Serialize, Serializer,
+    };
+
+    use crate::radix_trie::{Key, Node, RadixTrie, Trie};
+
+    /// Deepest trie that is deserialized, in nodes from the root to a leaf.
+    /// Deserializing recurses once for each node on the way down, so deeper
+    /// input is rejected rather than overflowing the stack. As each node has
+    /// a non-empty key, this fits any trie of keys up to 256 units long (e.g.
+    /// IPv6 prefixes as `BitKey`s).
+    pub const MAX_DEPTH: usize = 256;
+
+    pub fn serialize<P, V, S>(trie: &RadixTrie<P, V>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        P: Serialize,
+        V: Serialize,
+        S: Serializer,
+    {
+        trie.root.serialize(serializer)
+    }
+
+    pub fn deserialize<'de, P, V, D>(deserializer: D) -> Result<RadixTrie<P, V>, D::Error>
+    where
+        P: Key + Deserialize<'de>,
+        V: Deserialize<'de>,
+        D: Deserializer<'de>,
+    {
+        let root = TrieSeed::new(0).deserialize(deserializer)?;
+        root.check_structure_invariants()
+            .map_err(D::Error::custom)?;
+        let len = root.count_values();
+        Ok(RadixTrie { root, len })
+    }
+
+    #[derive(Deserialize)]
+    #[serde(field_identifier, rename_all = "lowercase")]
+    enum TrieField {
+        Value,
+        Nodes,
+        #[serde(other)]
+        Ignore,
+    }
+
+    #[derive(Deserialize)]
+    #[serde(field_identifier, rename_all = "lowercase")]
+    enum NodeField {
+        Key,
+        Trie,
+        #[serde(other)]
+        Ignore,
+    }
+
+    /// Deserializes a [Trie] that is `depth` nodes below the root, in the
+    /// same form as a derived `Deserialize` would
+    struct TrieSeed<P, V> {
+        depth: usize,
+        marker: PhantomData<Trie<P, V>>,
+    }
+
+    impl<P, V> TrieSeed<P, V> {
+        fn new(depth: usize) -> Self {
+            TrieSeed {
+                depth,
+                marker: PhantomData,
+            }
+        }
+    }
+
+    impl<'de, P, V> DeserializeSeed<'de> for TrieSeed<P, V>
+    where
+        P: Deserialize<'de>,
+        V: Deserialize<'de>,
+    {
+        type Value = Trie<P, V>;
+
+        fn deserialize<D>(self, deserializer: D) -> Result<Trie<P, V>, D::Error>
+        where
+            D: Deserializer<'de>,
+        {
+            if self.depth > MAX_DEPTH {
+                return Err(D::Error::custom(format_args!(
+                    "trie is deeper than the maximum depth of {}",
+                    MAX_DEPTH
+                )));
+            }
+            deserializer.deserialize_struct("Trie", &["value", "nodes"], self)
+        }
+    }
+
+    impl<'de, P, V> Visitor<'de> for TrieSeed<P, V>
+    where
+        P: Deserialize<'de>,
+        V: Deserialize<'de>,
+    {
+        type Value = Trie<P, V>;
+
+        fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            f.write_str("struct Trie")
+        }
+
+        fn visit_seq<A>(self, mut seq: A) -> Result<Trie<P, V>, A::Error>
+        where
+            A: SeqAccess<'de>,
+        {
+            let depth = self.depth;
+            let value = seq
+                .next_element()?
+                .ok_or_else(|| A::Error::invalid_length(0, &self))?;
+            let nodes = seq
+                .next_element_seed(NodesSeed::new(depth))?
+                .ok_or_else(|| A::Error::invalid_length(1, &self))?;
+            Ok(Trie { value, nodes })
+        }
+
+        fn visit_map<A>(self, mut map: A) -> Result<Trie<P, V>, A::Error>
+        where
+            A: MapAccess<'de>,
+        {
+            let mut value = None;
+            let mut nodes = None;
+            while let Some(field) = map.next_key()? {
+                match field {
+                    TrieField::Value if value.is_some() => {
+                        return Err(A::Error::duplicate_field("value"));
+                    }
+                    TrieField::Value => value = Some(map.next_value()?),
+                    TrieField::Nodes if nodes.is_some() => {
+                        return Err(A::Error::duplicate_field("nodes"));
+                    }
+                    TrieField::Nodes => {
+                        nodes = Some(map.next_value_seed(NodesSeed::new(self.depth))?)
+                    }
+                    TrieField::Ignore => {
+                        map.next_value::<IgnoredAny>()?;
+                    }
+                }
+            }
+            Ok(Trie {
+                // a missing `Option` is `None`, as for a derived `Deserialize`
+                value: value.unwrap_or(None),
+                nodes: nodes.ok_or_else(|| A::Error::missing_field("nodes"))?,
+            })
+        }
+    }
+
+    /// Deserializes the child [Node]s of a trie that is `depth` nodes below
+    /// the root
+    struct NodesSeed<P, V> {
+        depth: usize,
+        marker: PhantomData<Node<P, V>>,
+    }
+
+    impl<P, V> NodesSeed<P, V> {
+        fn new(depth: usize) -> Self {
+            NodesSeed {
+                depth,
+                marker: PhantomData,
+            }
+        }
+    }
+
+    impl<'de, P, V> DeserializeSeed<'de> for NodesSeed<P, V>
+    where
+        P: Deserialize<'de>,
+        V: Deserialize<'de>,
+    {
+        type Value = Vec<Node<P, V>>;
+
+        fn deserialize<D>(self, deserializer: D) -> Result<Vec<Node<P, V>>, D::Error>
+        where
+            D: Deserializer<'de>,
+        {
+            deserializer.deserialize_seq(self)
+        }
+    }
+
+    impl<'de, P, V> Visitor<'de> for NodesSeed<P, V>
+    where
+        P: Deserialize<'de>,
+        V: Deserialize<'de>,
+    {
+        type Value = Vec<Node<P, V>>;
+
+        fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            f.write_str("a sequence of nodes")
+        }
+
+        fn visit_seq<A>(self, mut seq: A) -> Result<Vec<Node<P, V>>, A::Error>
+        where
+            A: SeqAccess<'de>,
+        {
+            let mut nodes = vec![];
+            while let Some(node) = seq.next_element_seed(NodeSeed::new(self.depth + 1))? {
+                nodes.push(node);
+            }
+            Ok(nodes)
+        }
+    }
+
+    /// Deserializes a [Node], whose trie is `depth` nodes below the root
+    struct NodeSeed<P, V> {
+        depth: usize,
+        marker: PhantomData<Node<P, V>>,
+    }
+
+    impl<P, V> NodeSeed<P, V> {
+        fn new(depth: usize) -> Self {
+            NodeSeed {
+                depth,
+                marker: PhantomData,
+            }
+        }
+    }
+
+    impl<'de, P, V> DeserializeSeed<'de> for NodeSeed<P, V>
+    where
+        P: Deserialize<'de>,
+        V: Deserialize<'de>,
+    {
+        type Value = Node<P, V>;
+
+        fn deserialize<D>(self, deserializer: D) -> Result<Node<P, V>, D::Error>
+        where
+            D: Deserializer<'de>,
+        {
+            deserializer.deserialize_struct("Node", &["key", "trie"], self)
+        }
+    }
+
+    impl<'de, P, V> Visitor<'de> for NodeSeed<P, V>
+    where
+        P: Deserialize<'de>,
+        V: Deserialize<'de>,
+    {
+        type Value = Node<P, V>;
+
+        fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            f.write_str("struct Node")
+        }
+
+        fn visit_seq<A>(self, mut seq: A) -> Result<Node<P, V>, A::Error>
+        where
+            A: SeqAccess<'de>,
+        {
+            let depth = self.depth;
+            let key = seq
+                .next_element()?
+                .ok_or_else(|| A::Error::invalid_length(0, &self))?;
+            let trie = seq
+                .next_element_seed(TrieSeed::new(depth))?
+                .ok_or_else(|| A::Error::invalid_length(1, &self))?;
+            Ok(Node { key, trie })
+        }
+
+        fn visit_map<A>(self, mut map: A) -> Result<Node<P, V>, A::Error>
+        where
+            A: MapAccess<'de>,
+        {
+            let mut key = None;
+            let mut trie = None;
+            while let Some(field) = map.next_key()? {
+                match field {
+                    NodeField::Key if key.is_some() => {
+                        return Err(A::Error::duplicate_field("key"));
+                    }
+                    NodeField::Key => key = Some(map.next_value()?),
+                    NodeField::Trie if trie.is_some() => {
+                        return Err(A::Error::duplicate_field("trie"));
+                    }
+                    NodeField::Trie => trie = Some(map.next_value_seed(TrieSeed::new(self.depth))?),
+                    NodeField::Ignore => {
+                        map.next_value::<IgnoredAny>()?;
+                    }
+                }
+            }
+            Ok(Node {
+                key: key.ok_or_else(|| A::Error::missing_field("key"))?,
+                trie: trie.ok_or_else(|| A::Error::missing_field("trie"))?,
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashMap;
+
+    use itertools::assert_equal;
+    use serde::{Deserialize, Serialize};
+
+    use crate::radix_trie::RadixTrie;
+
+    #[derive(Serialize, Deserialize)]
+    struct Structural {
+        #[serde(with = "super::structural")]
+        trie: RadixTrie<String, usize>,
+    }
+
+    fn get_test_trie() -> RadixTrie<String, usize> {
+        let mut trie = RadixTrie::new();
+        trie.insert("dog".into(), 1);
+        trie.insert("dots".into(), 2);
+        trie.insert("".into(), 3);
+        trie
+    }
+
+    #[test]
+    fn test_map() {
+        let json = serde_json::to_string(&get_test_trie()).unwrap();
+        assert_eq!(r#"{"":3,"dog":1,"dots":2}"#, json);
+
+        let trie: RadixTrie<String, usize> = serde_json::from_str(&json).unwrap();
+        assert_equal(get_test_trie().iter(), trie.iter());
+        assert_eq!(3, trie.len());
+
+        // interoperable with HashMap
+        let map: HashMap<String, usize> = serde_json::from_str(&json).unwrap();
+        let trie: RadixTrie<String, usize> =
+            serde_json::from_str(&serde_json::to_string(&map).unwrap()).unwrap();
+        assert_equal(get_test_trie().iter(), trie.iter());
+    }
+
+    #[test]
+    fn test_structural() {
+        let json = serde_json::to_string(&Structural {
+            trie: get_test_trie(),
+        })
+        .unwrap();
+        assert_eq!(
+            concat!(
+                r#"{"trie":{"value":3,"nodes":[{"key":"do","trie":{"value":null,"nodes":["#,
+                r#"{"key":"g","trie":{"value":1,"nodes":[]}},"#,
+                r#"{"key":"ts","trie":{"value":2,"nodes":[]}}]}}]}}"#
+            ),
+            json
+        );
+
+        let Structural { mut trie } = serde_json::from_str(&json).unwrap();
+        assert_equal(get_test_trie().iter(), trie.iter());
+        assert_eq!(3, trie.len());
+        trie.insert("do".into(), 4);
+        assert_eq!(Some(&4), trie.get("do"));
+
+        // structs as sequences, as in formats such as bincode
+        let json = r#"{"trie":[3,[["do",[null,[["g",[1,[]]],["ts",[2,[]]]]]]]]}"#;
+        let Structural { trie } = serde_json::from_str(json).unwrap();
+        assert_equal(get_test_trie().iter(), trie.iter());
+    }
+
+    #[test]
+    fn test_structural_rejects_malformed() {
+        let parse = |trie: &str| {
+            serde_json::from_str::<Structural>(&format!(r#"{{"trie":{}}}"#, trie))
+                .err()
+                .map(|err| err.to_string())
+        };
+        let leaf = |key: &str| format!(r#"{{"key":"{}","trie":{{"value":1,"nodes":[]}}}}"#, key);
+
+        assert_eq!(None, parse(r#"{"value":null,"nodes":[]}"#));
+        assert_eq!(
+            None,
+            parse(&format!(
+                r#"{{"value":null,"nodes":[{},{}]}}"#,
+                leaf("a"),
+                leaf("b")
+            ))
+        );
+
+        let err = parse(&format!(
+            r#"{{"value":null,"nodes":[{},{}]}}"#,
+            leaf("ab"),
+            leaf("ac")
+        ));
+        assert!(err.unwrap().contains("no shared prefixes invariant failed"));
+
+        let err = parse(&format!(
+            r#"{{"value":null,"nodes":[{},{}]}}"#,
+            leaf("b"),
+            leaf("a")
+        ));
+        assert!(err.unwrap().contains("sorted children invariant failed"));
+
+        let err = parse(r#"{"value":null,"nodes":[{"key":"a","trie":{"value":null,"nodes":[]}}]}"#);
+        assert!(err.unwrap().contains("leaf node Some(_) invariant failed"));
+
+        let err = parse(&format!(r#"{{"value":null,"nodes":[{}]}}"#, leaf("")));
+        assert!(err.unwrap().contains("non-empty key invariant failed"));
+
+        let err = parse(&format!(
+            r#"{{"value":null,"nodes":[{{"key":"a","trie":{{"value":null,"nodes":[{}]}}}}]}}"#,
+            leaf("b")
+        ));
+        assert!(err.unwrap().contains("compressed node invariant failed"));
+    }
+
+    #[test]
+    fn test_structural_wide() {
+        #[derive(Deserialize)]
+        struct Wide {
+            #[serde(with = "super::structural")]
+            trie: RadixTrie<Vec<u32>, u8>,
+        }
+
+        // a root with many children, which are checked without comparing
+        // every pair of them
+        const WIDTH: u32 = 30_000;
+        let parse = |extra: &str| {
+            let nodes: Vec<_> = (0..WIDTH)
+                .map(|unit| format!(r#"{{"key":[{}],"trie":{{"value":1,"nodes":[]}}}}"#, unit))
+                .chain((!extra.is_empty()).then(|| extra.to_owned()))
+                .collect();
+            let json = format!(
+                r#"{{"trie":{{"value":null,"nodes":[{}]}}}}"#,
+                nodes.join(",")
+            );
+            serde_json::from_str::<Wide>(&json).map_err(|err| err.to_string())
+        };
+
+        let Wide { trie } = parse("").unwrap();
+        assert_eq!(WIDTH as usize, trie.len());
+        assert_eq!(Some(&1), trie.get(&[WIDTH - 1][..]));
+
+        let shared = format!(
+            r#"{{"key":[{},1],"trie":{{"value":1,"nodes":[]}}}}"#,
+            WIDTH - 1
+        );
+        let err = parse(&shared).err().unwrap();
+        assert!(err.contains("no shared prefixes invariant failed"));
+
+        let unsorted = r#"{"key":[0,1],"trie":{"value":1,"nodes":[]}}"#;
+        let err = parse(unsorted).err().unwrap();
+        assert!(err.contains("sorted children invariant failed"));
+    }
+
+    #[test]
+    fn test_structural_deep() {
+        use super::structural::{self, MAX_DEPTH};
+
+        // a trie of keys [0], [0, 0], ..., with `depth` nodes below the root
+        let parse = |depth: usize| {
+            let json = format!(
+                "{}{}{}",
+                r#"{"value":1,"nodes":[{"key":[0],"trie":"#.repeat(depth),
+                r#"{"value":1,"nodes":[]}"#,
+                "}]}".repeat(depth)
+            );
+            // without serde_json's own limit, which would reject the input
+            // before `structural` does
+            let mut deserializer = serde_json::Deserializer::from_str(&json);
+            deserializer.disable_recursion_limit();
+            structural::deserialize::<Vec<u32>, u8, _>(&mut deserializer)
+                .map_err(|err| err.to_string())
+        };
+
+        let trie = parse(MAX_DEPTH).unwrap();
+        assert_eq!(MAX_DEPTH + 1, trie.len());
+        assert_eq!(Some(&1), trie.get(&vec![0; MAX_DEPTH][..]));
+
+        let err = parse(MAX_DEPTH + 1).err().unwrap();
+        assert!(err.contains("trie is deeper than the maximum depth of 256"));
+
+        // rejected on the way down, before it can overflow the stack
+        let err = parse(1_000_000).err().unwrap();
+        assert!(err.contains("trie is deeper than the maximum depth of 256"));
+    }
+}